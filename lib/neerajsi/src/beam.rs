@@ -0,0 +1,484 @@
+use std::collections::BTreeMap;
+
+use num_traits::FromPrimitive;
+
+use crate::{index2d_array, CardinalDirectionName, Grid, Location, Map, CARDINAL_DIRECTIONS};
+
+/// What a tile does to a beam entering it with a given heading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeamBehavior {
+    Pass,
+    Reflect(CardinalDirectionName),
+    Split([CardinalDirectionName; 2]),
+    /// Moves the beam into the two side cells while keeping its heading (2025 day7 splitters).
+    Fork([CardinalDirectionName; 2]),
+    Absorb,
+}
+
+/// A beam entering `Location` travelling in the given direction.
+pub type BeamState = (Location, CardinalDirectionName);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Node(usize),
+    Exit(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct BeamExit {
+    pub location: Location,
+    pub direction: CardinalDirectionName,
+    pub beams: usize,
+    pub timelines: Option<u128>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BeamTrace {
+    pub energized: Map<bool>,
+    pub energized_count: usize,
+    pub splits: usize,
+    pub exits: Vec<BeamExit>,
+}
+
+impl BeamTrace {
+    /// Number of distinct paths that leave the grid, or `None` if the beams loop.
+    pub fn total_timelines(&self) -> Option<u128> {
+        self.exits.iter().map(|e| e.timelines).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EntrySummary {
+    pub entry: BeamState,
+    pub energized_count: usize,
+    pub exits: Vec<BeamState>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BeamEngine {
+    grid: Grid,
+    outs: Vec<[Option<Step>; 2]>,
+    splits: Vec<bool>,
+}
+
+enum Reach {
+    Chain {
+        cell: usize,
+        exit: Option<usize>,
+        next: Option<usize>,
+    },
+    Junction(Vec<u64>),
+}
+
+impl BeamEngine {
+    pub fn new<S, T, F>(map: &[S], behavior: F) -> Self
+    where
+        S: AsRef<[T]>,
+        F: Fn(&T, CardinalDirectionName) -> BeamBehavior,
+    {
+        use BeamBehavior::*;
+
+        let grid = Grid::from_map(map);
+        let node_count = grid.cell_count() * 4;
+        let mut outs = vec![[None; 2]; node_count];
+        let mut splits = vec![false; node_count];
+
+        for loc in grid.cell_range() {
+            let tile = &index2d_array!(map, loc);
+            for dir in CARDINAL_DIRECTIONS {
+                let steps = match behavior(tile, dir) {
+                    Pass => [Some((dir, dir)), None],
+                    Reflect(d) => [Some((d, d)), None],
+                    Split([a, b]) => [Some((a, a)), Some((b, b))],
+                    Fork([a, b]) => [Some((a, dir)), Some((b, dir))],
+                    Absorb => [None, None],
+                };
+
+                let node = node_index(&grid, loc, dir);
+                splits[node] = steps[1].is_some();
+                outs[node] = steps.map(|s| {
+                    s.map(|(step, heading)| match grid.add_cardinal(loc, step) {
+                        Some(next) => Step::Node(node_index(&grid, next, heading)),
+                        None => Step::Exit(node_index(&grid, loc, step)),
+                    })
+                });
+            }
+        }
+
+        Self { grid, outs, splits }
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    fn node(&self, state: BeamState) -> usize {
+        node_index(&self.grid, state.0, state.1)
+    }
+
+    fn state(&self, node: usize) -> BeamState {
+        let cell = node / 4;
+        (
+            [cell / self.grid.cols(), cell % self.grid.cols()],
+            CardinalDirectionName::from_usize(node % 4).unwrap(),
+        )
+    }
+
+    pub fn trace(&self, start: BeamState) -> BeamTrace {
+        let start = self.node(start);
+        let mut visited = vec![false; self.outs.len()];
+        let mut reached = Vec::new();
+        let mut exit_beams = BTreeMap::new();
+
+        visited[start] = true;
+        let mut stack = vec![start];
+        while let Some(n) = stack.pop() {
+            reached.push(n);
+            for step in self.outs[n].iter().flatten() {
+                match *step {
+                    Step::Node(m) => {
+                        if !visited[m] {
+                            visited[m] = true;
+                            stack.push(m);
+                        }
+                    }
+                    Step::Exit(e) => *exit_beams.entry(e).or_insert(0) += 1,
+                }
+            }
+        }
+
+        let mut energized = self.grid.new_map(false);
+        for &n in &reached {
+            let loc = self.state(n).0;
+            energized[loc[0]][loc[1]] = true;
+        }
+
+        let energized_count = energized.iter().flatten().filter(|&&e| e).count();
+        let splits = reached.iter().filter(|&&n| self.splits[n]).count();
+        let timelines = self.count_timelines(start, &reached);
+
+        let exits = exit_beams
+            .into_iter()
+            .map(|(e, beams)| {
+                let (location, direction) = self.state(e);
+                BeamExit {
+                    location,
+                    direction,
+                    beams,
+                    timelines: timelines.as_ref().map(|t| t.get(&e).copied().unwrap_or(0)),
+                }
+            })
+            .collect();
+
+        BeamTrace { energized, energized_count, splits, exits }
+    }
+
+    // Kahn's algorithm over the reachable states; a leftover state means the beams loop.
+    fn count_timelines(&self, start: usize, reached: &[usize]) -> Option<BTreeMap<usize, u128>> {
+        let mut in_degree = vec![0usize; self.outs.len()];
+        for &n in reached {
+            for step in self.outs[n].iter().flatten() {
+                if let Step::Node(m) = *step {
+                    in_degree[m] += 1;
+                }
+            }
+        }
+
+        if in_degree[start] != 0 {
+            return None;
+        }
+
+        let mut paths = vec![0u128; self.outs.len()];
+        let mut exit_paths = BTreeMap::new();
+        let mut processed = 0;
+
+        paths[start] = 1;
+        let mut ready = vec![start];
+        while let Some(n) = ready.pop() {
+            processed += 1;
+            for step in self.outs[n].iter().flatten() {
+                match *step {
+                    Step::Node(m) => {
+                        paths[m] += paths[n];
+                        in_degree[m] -= 1;
+                        if in_degree[m] == 0 {
+                            ready.push(m);
+                        }
+                    }
+                    Step::Exit(e) => *exit_paths.entry(e).or_insert(0) += paths[n],
+                }
+            }
+        }
+
+        (processed == reached.len()).then_some(exit_paths)
+    }
+
+    pub fn entry_points(&self) -> Vec<BeamState> {
+        use CardinalDirectionName::*;
+
+        let (rows, cols) = (self.grid.rows(), self.grid.cols());
+        let mut entries = Vec::with_capacity(2 * (rows + cols));
+        for c in self.grid.col_range() {
+            entries.push(([0, c], S));
+            entries.push(([rows - 1, c], N));
+        }
+
+        for r in self.grid.row_range() {
+            entries.push(([r, 0], E));
+            entries.push(([r, cols - 1], W));
+        }
+
+        entries
+    }
+
+    /// Energized counts and exits for every boundary entry point. The state graph is
+    /// condensed into strongly connected components once, and each branching component
+    /// caches a bitset of everything it reaches so the entries share that work.
+    pub fn summarize_entries(&self) -> Vec<EntrySummary> {
+        let cells = self.grid.cell_count();
+        let exit_bits: BTreeMap<usize, usize> = self
+            .outs
+            .iter()
+            .flatten()
+            .flatten()
+            .filter_map(|s| match *s {
+                Step::Exit(e) => Some(e),
+                Step::Node(_) => None,
+            })
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| (e, cells + i))
+            .collect();
+
+        let words = (cells + exit_bits.len()).div_ceil(64);
+        let (comp, comp_count) = self.strongly_connected();
+
+        let mut members = vec![Vec::new(); comp_count];
+        for (n, &c) in comp.iter().enumerate() {
+            members[c].push(n);
+        }
+
+        fn collect(reach: &[Reach], mut c: Option<usize>, bits: &mut [u64]) {
+            while let Some(cur) = c {
+                match &reach[cur] {
+                    Reach::Chain { cell, exit, next } => {
+                        bits[cell / 64] |= 1 << (cell % 64);
+                        if let Some(e) = exit {
+                            bits[e / 64] |= 1 << (e % 64);
+                        }
+                        c = *next;
+                    }
+                    Reach::Junction(set) => {
+                        bits.iter_mut().zip(set).for_each(|(b, s)| *b |= s);
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Tarjan numbers components in reverse topological order, so successors come first.
+        let mut reach: Vec<Reach> = Vec::with_capacity(comp_count);
+        for (c, nodes) in members.iter().enumerate() {
+            let mut next = Vec::new();
+            let mut exits = Vec::new();
+            for &n in nodes {
+                for step in self.outs[n].iter().flatten() {
+                    match *step {
+                        Step::Node(m) if comp[m] != c => next.push(comp[m]),
+                        Step::Node(_) => {}
+                        Step::Exit(e) => exits.push(exit_bits[&e]),
+                    }
+                }
+            }
+
+            next.sort_unstable();
+            next.dedup();
+
+            if nodes.len() == 1 && next.len() <= 1 && exits.len() <= 1 {
+                reach.push(Reach::Chain {
+                    cell: nodes[0] / 4,
+                    exit: exits.first().copied(),
+                    next: next.first().copied(),
+                });
+                continue;
+            }
+
+            let mut bits = vec![0u64; words];
+            for &n in nodes {
+                let cell = n / 4;
+                bits[cell / 64] |= 1 << (cell % 64);
+            }
+
+            for e in exits {
+                bits[e / 64] |= 1 << (e % 64);
+            }
+
+            for t in next {
+                collect(&reach, Some(t), &mut bits);
+            }
+
+            reach.push(Reach::Junction(bits));
+        }
+
+        let bit_to_exit: BTreeMap<usize, usize> = exit_bits.iter().map(|(&e, &b)| (b, e)).collect();
+
+        self.entry_points()
+            .into_iter()
+            .map(|entry| {
+                let mut bits = vec![0u64; words];
+                collect(&reach, Some(comp[self.node(entry)]), &mut bits);
+
+                let mut energized_count = 0;
+                let mut exits = Vec::new();
+                for (w, &word) in bits.iter().enumerate() {
+                    let mut word = word;
+                    while word != 0 {
+                        let bit = w * 64 + word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        if bit < cells {
+                            energized_count += 1;
+                        } else {
+                            exits.push(self.state(bit_to_exit[&bit]));
+                        }
+                    }
+                }
+
+                EntrySummary { entry, energized_count, exits }
+            })
+            .collect()
+    }
+
+    fn strongly_connected(&self) -> (Vec<usize>, usize) {
+        const UNVISITED: usize = usize::MAX;
+
+        let n = self.outs.len();
+        let mut index = vec![UNVISITED; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut comp = vec![UNVISITED; n];
+        let mut stack = Vec::new();
+        let mut call: Vec<(usize, usize)> = Vec::new();
+        let mut next_index = 0;
+        let mut comp_count = 0;
+
+        for root in 0..n {
+            if index[root] != UNVISITED {
+                continue;
+            }
+
+            index[root] = next_index;
+            low[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            call.push((root, 0));
+
+            while let Some(&(v, i)) = call.last() {
+                if i < 2 {
+                    call.last_mut().unwrap().1 += 1;
+                    if let Some(Step::Node(w)) = self.outs[v][i] {
+                        if index[w] == UNVISITED {
+                            index[w] = next_index;
+                            low[w] = next_index;
+                            next_index += 1;
+                            stack.push(w);
+                            on_stack[w] = true;
+                            call.push((w, 0));
+                        } else if on_stack[w] {
+                            low[v] = low[v].min(index[w]);
+                        }
+                    }
+                    continue;
+                }
+
+                call.pop();
+                if let Some(&(p, _)) = call.last() {
+                    low[p] = low[p].min(low[v]);
+                }
+
+                if low[v] == index[v] {
+                    loop {
+                        let w = stack.pop().unwrap();
+                        on_stack[w] = false;
+                        comp[w] = comp_count;
+                        if w == v {
+                            break;
+                        }
+                    }
+                    comp_count += 1;
+                }
+            }
+        }
+
+        (comp, comp_count)
+    }
+}
+
+fn node_index(grid: &Grid, loc: Location, dir: CardinalDirectionName) -> usize {
+    (loc[0] * grid.cols() + loc[1]) * 4 + dir as usize
+}
+
+#[test]
+fn check_beams() {
+    use CardinalDirectionName::*;
+
+    let contraption = [
+        r".|...\....",
+        r"|.-.\.....",
+        r".....|-...",
+        r"........|.",
+        r"..........",
+        r".........\",
+        r"..../.\\..",
+        r".-.-/..|..",
+        r".|....-|.\",
+        r"..//.|....",
+    ].map(|l| l.as_bytes());
+
+    let engine = BeamEngine::new(&contraption, |&t, d| match (t, d) {
+        (b'|', W | E) => BeamBehavior::Split([N, S]),
+        (b'-', N | S) => BeamBehavior::Split([W, E]),
+        (b'/', _) => BeamBehavior::Reflect([S, N, E, W][d as usize]),
+        (b'\\', _) => BeamBehavior::Reflect([N, S, W, E][d as usize]),
+        _ => BeamBehavior::Pass,
+    });
+
+    assert_eq!(engine.trace(([0, 0], E)).energized_count, 46);
+
+    let summaries = engine.summarize_entries();
+    for s in &summaries {
+        let trace = engine.trace(s.entry);
+        assert_eq!(s.energized_count, trace.energized_count);
+        assert_eq!(s.exits.len(), trace.exits.len());
+    }
+    assert_eq!(summaries.iter().map(|s| s.energized_count).max(), Some(51));
+
+    let manifold = [
+        ".......S.......",
+        "...............",
+        ".......^.......",
+        "...............",
+        "......^.^......",
+        "...............",
+        ".....^.^.^.....",
+        "...............",
+        "....^.^...^....",
+        "...............",
+        "...^.^...^.^...",
+        "...............",
+        "..^...^.....^..",
+        "...............",
+        ".^.^.^.^.^...^.",
+        "...............",
+    ].map(|l| l.as_bytes());
+
+    let engine = BeamEngine::new(&manifold, |&t, _| match t {
+        b'^' => BeamBehavior::Fork([W, E]),
+        _ => BeamBehavior::Pass,
+    });
+
+    let trace = engine.trace(([0, 7], S));
+    assert_eq!(trace.splits, 21);
+    assert_eq!(trace.total_timelines(), Some(40));
+}
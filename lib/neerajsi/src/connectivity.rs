@@ -0,0 +1,142 @@
+use crate::{Grid, Location};
+
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> Self {
+        Self { parent: (0..n).collect(), size: vec![1; n] }
+    }
+
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+
+        x
+    }
+
+    /// Returns false if `a` and `b` were already in the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Separation {
+    /// The two locations are apart even before any insertion.
+    NeverConnected,
+    /// Still connected after every insertion.
+    AlwaysConnected,
+    /// Index of the insertion that first cuts them apart.
+    At(usize),
+}
+
+/// Finds the first of `insertions` that blocks every path between `a` and `b`.
+///
+/// Runs the insertions backwards as removals so each step is a few union-find operations
+/// instead of a whole new search.
+pub fn first_separating_insertion<S, T>(
+    map: &[S],
+    passable: impl Fn(&T) -> bool,
+    insertions: &[Location],
+    a: Location,
+    b: Location,
+) -> Separation
+where
+    S: AsRef<[T]>,
+{
+    let grid = Grid::from_map(map);
+    let cell = |loc: Location| loc[0] * grid.cols() + loc[1];
+
+    let mut open: Vec<bool> = grid.cell_range().map(|[r, c]| passable(&map[r].as_ref()[c])).collect();
+    let mut blocked_count = vec![0u32; grid.cell_count()];
+    for &loc in insertions {
+        blocked_count[cell(loc)] += 1;
+    }
+
+    let base_open = open.clone();
+    for (o, &count) in open.iter_mut().zip(&blocked_count) {
+        *o &= count == 0;
+    }
+
+    let mut uf = UnionFind::new(grid.cell_count());
+    let join = |loc: Location, open: &[bool], uf: &mut UnionFind| {
+        for n in grid.neighbors_cardinal(loc) {
+            if open[cell(n)] {
+                uf.union(cell(loc), cell(n));
+            }
+        }
+    };
+
+    for loc in grid.cell_range() {
+        if open[cell(loc)] {
+            join(loc, &open, &mut uf);
+        }
+    }
+
+    let is_connected = |open: &[bool], uf: &mut UnionFind| {
+        open[cell(a)] && open[cell(b)] && uf.connected(cell(a), cell(b))
+    };
+
+    if is_connected(&open, &mut uf) {
+        return Separation::AlwaysConnected;
+    }
+
+    for (i, &loc) in insertions.iter().enumerate().rev() {
+        let c = cell(loc);
+        blocked_count[c] -= 1;
+        if blocked_count[c] == 0 && base_open[c] {
+            open[c] = true;
+            join(loc, &open, &mut uf);
+        }
+
+        if is_connected(&open, &mut uf) {
+            return Separation::At(i);
+        }
+    }
+
+    Separation::NeverConnected
+}
+
+#[test]
+fn check_first_blocking_byte() {
+    let bytes = [
+        [5, 4], [4, 2], [4, 5], [3, 0], [2, 1], [6, 3], [2, 4], [1, 5], [0, 6], [3, 3], [2, 6], [5, 1], [1, 2],
+        [5, 5], [2, 5], [6, 5], [1, 4], [0, 4], [6, 4], [1, 1], [6, 1], [1, 0], [0, 5], [1, 6], [2, 0],
+    ];
+
+    let insertions: Vec<Location> = bytes.iter().map(|&[x, y]| [y, x]).collect();
+    let memory = vec![vec![true; 7]; 7];
+
+    let separation = first_separating_insertion(&memory, |&free| free, &insertions, [0, 0], [6, 6]);
+    assert_eq!(separation, Separation::At(20));
+    assert_eq!(bytes[20], [6, 1]);
+
+    let separation = first_separating_insertion(&memory, |&free| free, &insertions[..20], [0, 0], [6, 6]);
+    assert_eq!(separation, Separation::AlwaysConnected);
+}
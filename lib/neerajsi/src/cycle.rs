@@ -0,0 +1,304 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use thiserror::Error;
+
+use crate::numtheory::{checked_lcm, crt_pair};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Maps step `n` onto the first occurrence of the same state.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.prefix {
+            n
+        } else {
+            self.prefix + (n - self.prefix) % self.period
+        }
+    }
+}
+
+/// The states visited up to the first repeat, plus the cycle they fall into.
+#[derive(Debug, Clone)]
+pub struct CycleHistory<S> {
+    pub cycle: Cycle,
+    history: Vec<S>,
+}
+
+impl<S> CycleHistory<S> {
+    pub fn state_at(&self, n: usize) -> &S {
+        &self.history[self.cycle.reduce(n)]
+    }
+
+    pub fn history(&self) -> &[S] {
+        &self.history
+    }
+}
+
+pub fn detect_cycle<S, F>(start: S, step: F) -> CycleHistory<S>
+where
+    S: Clone + Hash + Eq,
+    F: FnMut(&S) -> S,
+{
+    detect_cycle_by_key(start, step, |s| s.clone())
+}
+
+/// Like `detect_cycle`, but states are compared by `key`, e.g. a hash of the part that matters.
+pub fn detect_cycle_by_key<S, K, F, G>(start: S, step: F, key: G) -> CycleHistory<S>
+where
+    K: Hash + Eq,
+    F: FnMut(&S) -> S,
+    G: Fn(&S) -> K,
+{
+    let (history, cycle) = walk_until_repeat(start, step, key, usize::MAX);
+    CycleHistory { cycle: cycle.expect("states never repeated"), history }
+}
+
+/// Steps from `start` until a state repeats or `limit` steps have been taken. The history
+/// holds each distinct state once, so on a repeat the last state is the cycle's end.
+fn walk_until_repeat<S, K, F, G>(start: S, mut step: F, key: G, limit: usize) -> (Vec<S>, Option<Cycle>)
+where
+    K: Hash + Eq,
+    F: FnMut(&S) -> S,
+    G: Fn(&S) -> K,
+{
+    let mut seen = HashMap::new();
+    let mut history = vec![start];
+
+    loop {
+        let n = history.len() - 1;
+        let current = &history[n];
+        if let Some(&prefix) = seen.get(&key(current)) {
+            history.pop();
+            return (history, Some(Cycle { prefix, period: n - prefix }));
+        }
+        if n == limit {
+            return (history, None);
+        }
+
+        seen.insert(key(current), n);
+        let next = step(current);
+        history.push(next);
+    }
+}
+
+/// Brent's algorithm: finds the cycle keeping only two states alive.
+pub fn find_cycle_brent<S, F>(start: &S, mut step: F) -> Cycle
+where
+    S: Clone + PartialEq,
+    F: FnMut(&S) -> S,
+{
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(start);
+
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    let mut tortoise = start.clone();
+    let mut hare = start.clone();
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+
+    Cycle { prefix, period }
+}
+
+/// The state after `n` steps, fast-forwarding over whole periods once a state repeats.
+/// Never takes more than `n` steps, so it also works on sequences that don't cycle.
+pub fn state_at<S, F>(start: &S, step: F, n: usize) -> S
+where
+    S: Clone + Hash + Eq,
+    F: FnMut(&S) -> S,
+{
+    let (mut history, cycle) = walk_until_repeat(start.clone(), step, |s| s.clone(), n);
+    match cycle {
+        Some(cycle) => history.swap_remove(cycle.reduce(n)),
+        None => history.pop().unwrap(),
+    }
+}
+
+/// When a walker over `(node, instruction index)` states lands on a target: at each step in
+/// `transient_hits`, and at every step `h + k * cycle.period` for `h` in `cycle_hits`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkerCycle {
+    pub cycle: Cycle,
+    pub transient_hits: Vec<usize>,
+    pub cycle_hits: Vec<usize>,
+}
+
+impl WalkerCycle {
+    pub fn is_target_at(&self, n: usize) -> bool {
+        if n < self.cycle.prefix {
+            self.transient_hits.contains(&n)
+        } else {
+            self.cycle_hits.contains(&self.cycle.reduce(n))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ArrivalError {
+    #[error("walker {0} never reaches a target")]
+    NeverArrives(usize),
+    #[error("the walkers are never on targets at the same time")]
+    NoCommonStep,
+    #[error("more than {0} combinations of target offsets")]
+    TooManyCombinations(usize),
+    #[error("the combined cycle length overflows")]
+    Overflow,
+}
+
+/// Follows `start` through `step(node, instruction index)`, with the instruction index
+/// wrapping after `instructions`, until the combined state repeats.
+pub fn analyze_walker<N, F, G>(start: N, instructions: usize, mut step: F, is_target: G) -> WalkerCycle
+where
+    N: Clone + Hash + Eq,
+    F: FnMut(&N, usize) -> N,
+    G: Fn(&N) -> bool,
+{
+    let history = detect_cycle((start, 0), |(node, i)| (step(node, *i), (i + 1) % instructions));
+    let cycle = history.cycle;
+    let (transient_hits, cycle_hits) = history
+        .history()
+        .iter()
+        .enumerate()
+        .filter(|(_, (node, _))| is_target(node))
+        .map(|(n, _)| n)
+        .partition(|&n| n < cycle.prefix);
+
+    WalkerCycle { cycle, transient_hits, cycle_hits }
+}
+
+/// Cap on the distinct arrival residues tracked by `first_simultaneous_arrival`.
+pub const MAX_ARRIVAL_RESIDUES: usize = 1 << 20;
+
+/// The first step at which every walker is on a target at once. Each combination of cycle
+/// offsets is solved with the Chinese remainder theorem, so targets needn't line up with
+/// the start of a cycle and a walker may pass several targets per period. The work is the
+/// number of distinct residues modulo the combined period, which can approach the product
+/// of every walker's target count; past `MAX_ARRIVAL_RESIDUES` this gives up with an error.
+pub fn first_simultaneous_arrival(walkers: &[WalkerCycle]) -> Result<u128, ArrivalError> {
+    if let Some(w) = walkers.iter().position(|w| w.transient_hits.is_empty() && w.cycle_hits.is_empty()) {
+        return Err(ArrivalError::NeverArrives(w));
+    }
+
+    // Before every walker has entered its cycle, just check each step.
+    let settled = walkers.iter().map(|w| w.cycle.prefix).max().unwrap_or(0);
+    if let Some(n) = (0..settled).find(|&n| walkers.iter().all(|w| w.is_target_at(n))) {
+        return Ok(n as u128);
+    }
+
+    // Every residue shares the same combined modulus. Walkers with fewer targets go first,
+    // which keeps the set small for longer and finds contradictions early.
+    let mut order: Vec<&WalkerCycle> = walkers.iter().collect();
+    order.sort_by_key(|w| w.cycle_hits.len());
+
+    let mut modulus = 1u128;
+    let mut residues = HashSet::from([0u128]);
+    for w in order {
+        let period = w.cycle.period as u128;
+        let combined = checked_lcm(modulus, period).ok_or(ArrivalError::Overflow)?;
+
+        // With the combined modulus known to fit, `None` only means the offsets disagree.
+        let mut next = HashSet::new();
+        for &r in &residues {
+            for &h in &w.cycle_hits {
+                if let Some((a, _)) = crt_pair((r, modulus), (h as u128, period)) {
+                    next.insert(a);
+                    if next.len() > MAX_ARRIVAL_RESIDUES {
+                        return Err(ArrivalError::TooManyCombinations(MAX_ARRIVAL_RESIDUES));
+                    }
+                }
+            }
+        }
+
+        modulus = combined;
+        if next.is_empty() {
+            return Err(ArrivalError::NoCommonStep);
+        }
+        residues = next;
+    }
+
+    let settled = settled as u128;
+    residues
+        .into_iter()
+        .map(|a| if a >= settled { a } else { a + (settled - a).div_ceil(modulus) * modulus })
+        .min()
+        .ok_or(ArrivalError::NoCommonStep)
+}
+
+#[test]
+fn check_cycles() {
+    let step = |x: &u64| (x * x + 1) % 255;
+
+    let history = detect_cycle(3, step);
+    let brent = find_cycle_brent(&3, step);
+    assert_eq!(history.cycle, brent);
+
+    let mut state = 3;
+    for n in 0..100 {
+        assert_eq!(*history.state_at(n), state);
+        state = step(&state);
+    }
+
+    let far = 1_000_000_000;
+    assert_eq!(state_at(&3, step, far), *history.state_at(far));
+
+    // A counter never repeats, so only the steps asked for are taken.
+    assert_eq!(state_at(&0u64, |x| x + 1, 3), 3);
+    assert_eq!(state_at(&7u64, |x| x + 1, 0), 7);
+}
+
+#[test]
+fn check_ghost_walkers() {
+    let instructions = b"LR";
+    let network: HashMap<&str, (&str, &str)> = [
+        ("11A", ("11B", "XXX")), ("11B", ("XXX", "11Z")), ("11Z", ("11B", "XXX")), ("22A", ("22B", "XXX")),
+        ("22B", ("22C", "22C")), ("22C", ("22Z", "22Z")), ("22Z", ("22B", "22B")), ("XXX", ("XXX", "XXX")),
+    ].into_iter().collect();
+
+    let step = |node: &&str, i: usize| if instructions[i] == b'L' { network[node].0 } else { network[node].1 };
+    let walkers: Vec<WalkerCycle> = ["11A", "22A"]
+        .into_iter()
+        .map(|start| analyze_walker(start, instructions.len(), step, |n| n.ends_with('Z')))
+        .collect();
+
+    assert_eq!(walkers[1].cycle, Cycle { prefix: 1, period: 6 });
+    assert_eq!(walkers[1].cycle_hits, [3, 6]);
+    assert_eq!(first_simultaneous_arrival(&walkers), Ok(6));
+
+    // Counters modulo 4 and 6: targets that never coincide, and ones that need the CRT.
+    let counter = |m: usize, targets: &'static [usize]| analyze_walker(0, 1, move |n, _| (n + 1) % m, move |n| targets.contains(n));
+    assert_eq!(first_simultaneous_arrival(&[counter(4, &[1, 3]), counter(6, &[0, 2, 4])]), Err(ArrivalError::NoCommonStep));
+    assert_eq!(first_simultaneous_arrival(&[counter(4, &[3]), counter(6, &[5])]), Ok(11));
+    assert_eq!(first_simultaneous_arrival(&[counter(4, &[3]), counter(5, &[])]), Err(ArrivalError::NeverArrives(1)));
+
+    // Coprime periods with every step a target: the residues multiply up to 11 * 13 * ... .
+    let busy: Vec<WalkerCycle> = [11, 13, 17, 19, 23, 29].iter().map(|&p| analyze_walker(0, 1, move |n, _| (n + 1) % p, |_| true)).collect();
+    assert_eq!(first_simultaneous_arrival(&busy[..3]), Ok(0));
+    assert_eq!(first_simultaneous_arrival(&busy), Err(ArrivalError::TooManyCombinations(MAX_ARRIVAL_RESIDUES)));
+
+    // A walker whose only target is in its transient prefix.
+    let once = analyze_walker(0usize, 1, |n, _| (n + 1).min(9), |&n| n == 7);
+    assert_eq!(first_simultaneous_arrival(&[once, counter(4, &[3])]), Ok(7));
+}
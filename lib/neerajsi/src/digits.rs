@@ -0,0 +1,236 @@
+use std::ops::RangeInclusive;
+
+use crate::numtheory::gcd;
+
+pub const POW10_U64: [u64; 20] = {
+    let mut table = [1; 20];
+    let mut i = 1;
+    while i < 20 {
+        table[i] = table[i - 1] * 10;
+        i += 1;
+    }
+    table
+};
+
+pub const POW10_U128: [u128; 39] = {
+    let mut table = [1; 39];
+    let mut i = 1;
+    while i < 39 {
+        table[i] = table[i - 1] * 10;
+        i += 1;
+    }
+    table
+};
+
+/// Decimal digit manipulation on unsigned integers, without going through strings.
+pub trait Digits: Sized + Copy {
+    /// Number of decimal digits; zero has one.
+    fn digit_count(self) -> u32;
+
+    /// `10^n`, or `None` if it doesn't fit.
+    fn pow10(n: u32) -> Option<Self>;
+
+    /// The digits of `self` followed by the digits of `other`, e.g. `12 || 345 = 12345`.
+    fn concat(self, other: Self) -> Option<Self>;
+
+    /// Undoes `concat`: `12345.strip_suffix(345) == Some(12)`.
+    fn strip_suffix(self, suffix: Self) -> Option<Self>;
+
+    /// Splits the digits into `k` equal-length chunks, most significant first. Chunks after the
+    /// first may have had leading zeros (`9009` splits into `[90, 9]`).
+    fn split_chunks(self, k: u32) -> Option<Vec<Self>>;
+
+    fn split_half(self) -> Option<[Self; 2]> {
+        self.split_chunks(2).map(|c| [c[0], c[1]])
+    }
+
+    /// The block that `self` consists of `k` copies of, e.g. `123123` is `123` twice.
+    fn repeated_block(self, k: u32) -> Option<Self>;
+
+    fn is_repeated(self, k: u32) -> bool {
+        self.repeated_block(k).is_some()
+    }
+
+    /// `block` written out `k` times.
+    fn repeat(block: Self, k: u32) -> Option<Self>;
+}
+
+macro_rules! impl_digits {
+    ($t:ty, $table:ident) => {
+        impl Digits for $t {
+            fn digit_count(self) -> u32 {
+                self.checked_ilog10().map_or(1, |d| d + 1)
+            }
+
+            fn pow10(n: u32) -> Option<Self> {
+                $table.get(n as usize).copied()
+            }
+
+            fn concat(self, other: Self) -> Option<Self> {
+                self.checked_mul(Self::pow10(other.digit_count())?)?.checked_add(other)
+            }
+
+            fn strip_suffix(self, suffix: Self) -> Option<Self> {
+                let len = suffix.digit_count();
+                if len > self.digit_count() {
+                    return None;
+                }
+
+                let scale = Self::pow10(len)?;
+                (self % scale == suffix).then(|| self / scale)
+            }
+
+            fn split_chunks(self, k: u32) -> Option<Vec<Self>> {
+                let count = self.digit_count();
+                if k == 0 || count % k != 0 {
+                    return None;
+                }
+
+                let scale = Self::pow10(count / k)?;
+                let mut chunks = Vec::with_capacity(k as usize);
+                let mut rest = self;
+                for _ in 0..k {
+                    chunks.push(rest % scale);
+                    rest /= scale;
+                }
+                chunks.reverse();
+                Some(chunks)
+            }
+
+            fn repeated_block(self, k: u32) -> Option<Self> {
+                let chunks = self.split_chunks(k)?;
+                chunks.iter().all(|&c| c == chunks[0]).then_some(chunks[0])
+            }
+
+            fn repeat(block: Self, k: u32) -> Option<Self> {
+                let scale = Self::pow10(block.digit_count())?;
+                let mut result: Self = 0;
+                for _ in 0..k {
+                    result = result.checked_mul(scale)?.checked_add(block)?;
+                }
+                Some(result)
+            }
+        }
+    };
+}
+
+impl_digits!(u64, POW10_U64);
+impl_digits!(u128, POW10_U128);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repetitions {
+    Exactly(u32),
+    AtLeast(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RepeatedTotals {
+    pub count: u128,
+    pub sum: u128,
+}
+
+/// Count and sum of the `len`-digit numbers in `lo..=hi` that are some block repeated `k`
+/// times: `block * 100..0100..01`, so just an arithmetic series of blocks.
+fn repeated_with(len: u32, k: u32, lo: u128, hi: u128) -> RepeatedTotals {
+    let block_len = len / k;
+    let multiplier = (POW10_U128[len as usize] - 1) / (POW10_U128[block_len as usize] - 1);
+
+    let first = lo.div_ceil(multiplier).max(POW10_U128[block_len as usize - 1]);
+    let last = (hi / multiplier).min(POW10_U128[block_len as usize] - 1);
+    if first > last {
+        return RepeatedTotals::default();
+    }
+
+    let count = last - first + 1;
+    RepeatedTotals { count, sum: (first + last) * count / 2 * multiplier }
+}
+
+/// Totals over the numbers in `range` made of a digit block repeated a number of times
+/// allowed by `repetitions`. A number like `111111` that repeats in several ways is
+/// counted once: being both `a`- and `b`-repeated means being `lcm(a, b)`-repeated, so
+/// inclusion–exclusion over the allowed counts removes the overlaps.
+pub fn repeated_block_numbers(range: RangeInclusive<u64>, repetitions: Repetitions) -> RepeatedTotals {
+    let (lo, hi) = (*range.start() as u128, *range.end() as u128);
+    let mut totals = RepeatedTotals::default();
+    if lo > hi {
+        return totals;
+    }
+
+    for len in (lo as u64).digit_count()..=(hi as u64).digit_count() {
+        let (lo, hi) = (lo.max(POW10_U128[len as usize - 1]), hi.min(POW10_U128[len as usize] - 1));
+        let allowed: Vec<u32> = (2..=len)
+            .filter(|k| len % k == 0)
+            .filter(|&k| match repetitions {
+                Repetitions::Exactly(n) => k == n,
+                Repetitions::AtLeast(n) => k >= n,
+            })
+            .collect();
+
+        for subset in 1..1u32 << allowed.len() {
+            let k = (0..allowed.len())
+                .filter(|i| subset & (1 << i) != 0)
+                .fold(1, |acc, i| acc / gcd(acc as u128, allowed[i] as u128) as u32 * allowed[i]);
+            let part = repeated_with(len, k, lo, hi);
+
+            if subset.count_ones() % 2 == 1 {
+                totals.count += part.count;
+                totals.sum += part.sum;
+            } else {
+                totals.count -= part.count;
+                totals.sum -= part.sum;
+            }
+        }
+    }
+
+    totals
+}
+
+#[test]
+fn check_digits() {
+    assert_eq!([0u64, 9, 10, 999, 1000, u64::MAX].map(Digits::digit_count), [1, 1, 2, 3, 4, 20]);
+    assert_eq!(u128::MAX.digit_count(), 39);
+    assert_eq!(u64::pow10(19), Some(10_000_000_000_000_000_000));
+    assert_eq!(u64::pow10(20), None);
+
+    // 2024 day07: 156 = 15 || 6, and working backwards strips the last operand.
+    assert_eq!(15u64.concat(6), Some(156));
+    assert_eq!(156u64.strip_suffix(6), Some(15));
+    assert_eq!(156u64.strip_suffix(56), Some(1));
+    assert_eq!(156u64.strip_suffix(7), None);
+    assert_eq!(10u64.concat(0), Some(100));
+    assert_eq!(u64::MAX.concat(1), None);
+
+    // 2024 day11: even-digit stones split in two.
+    assert_eq!(999u64.split_half(), None);
+    assert_eq!(9009u64.split_half(), Some([90, 9]));
+    assert_eq!(253000u64.split_half(), Some([253, 0]));
+
+    // 2025 day2: invalid IDs are a block repeated.
+    assert!(1188511885u64.is_repeated(2));
+    assert_eq!(824824824u64.repeated_block(3), Some(824));
+    assert!(!101u64.is_repeated(3));
+    assert_eq!(u64::repeat(12, 3), Some(121212));
+    assert_eq!(u128::repeat(9, 38), Some(u128::pow10(38).unwrap() - 1));
+    assert_eq!(u64::repeat(99, 11), None);
+}
+
+#[test]
+fn check_repeated_ids() {
+    let ranges = [
+        11..=22, 95..=115, 998..=1012, 1188511880..=1188511890, 222220..=222224, 1698522..=1698528, 446443..=446449,
+        38593856..=38593862, 565653..=565659, 824824821..=824824827, 2121212118..=2121212124,
+    ];
+
+    let total = |repetitions| ranges.iter().map(|r| repeated_block_numbers(r.clone(), repetitions).sum).sum::<u128>();
+    assert_eq!(total(Repetitions::Exactly(2)), 1227775554);
+    assert_eq!(total(Repetitions::AtLeast(2)), 4174379265);
+
+    let brute = |range: RangeInclusive<u64>, ok: &dyn Fn(u64) -> bool| {
+        let hits: Vec<u64> = range.filter(|&n| ok(n)).collect();
+        RepeatedTotals { count: hits.len() as u128, sum: hits.iter().map(|&n| n as u128).sum() }
+    };
+    let any = |n: u64| (2..=n.digit_count()).any(|k| n.is_repeated(k));
+    assert_eq!(repeated_block_numbers(1..=300_000, Repetitions::AtLeast(2)), brute(1..=300_000, &any));
+    assert_eq!(repeated_block_numbers(1..=300_000, Repetitions::Exactly(3)), brute(1..=300_000, &|n| n.is_repeated(3)));
+    assert_eq!(repeated_block_numbers(0..=u64::MAX, Repetitions::Exactly(20)).count, 1);
+}
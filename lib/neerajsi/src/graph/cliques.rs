@@ -0,0 +1,266 @@
+use petgraph::graphmap::{NodeTrait, UnGraphMap};
+
+use super::Adjacency;
+
+/// Undirected adjacency as one bitset row per node.
+#[derive(Debug, Clone)]
+pub struct BitAdjacency {
+    n: usize,
+    words: usize,
+    rows: Vec<u64>,
+}
+
+fn set(bits: &mut [u64], i: usize) {
+    bits[i / 64] |= 1 << (i % 64);
+}
+
+fn clear(bits: &mut [u64], i: usize) {
+    bits[i / 64] &= !(1 << (i % 64));
+}
+
+fn count(bits: &[u64]) -> usize {
+    bits.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+fn ones(bits: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    bits.iter().enumerate().flat_map(|(i, &w)| {
+        let mut w = w;
+        std::iter::from_fn(move || {
+            (w != 0).then(|| {
+                let bit = w.trailing_zeros() as usize;
+                w &= w - 1;
+                i * 64 + bit
+            })
+        })
+    })
+}
+
+fn and(a: &[u64], b: &[u64]) -> Vec<u64> {
+    a.iter().zip(b).map(|(x, y)| x & y).collect()
+}
+
+impl BitAdjacency {
+    pub fn new(n: usize) -> Self {
+        let words = n.div_ceil(64);
+        Self { n, words, rows: vec![0; n * words] }
+    }
+
+    /// Treats every directed edge of `graph` as undirected.
+    pub fn from_adjacency<G: Adjacency + ?Sized>(graph: &G) -> Self {
+        let mut adj = Self::new(graph.node_count());
+        for a in 0..graph.node_count() {
+            for &b in graph.neighbors(a) {
+                adj.add_edge(a, b);
+            }
+        }
+        adj
+    }
+
+    /// Returns the adjacency along with the node behind each dense id.
+    pub fn from_graph_map<N: NodeTrait, E>(graph: &UnGraphMap<N, E>) -> (Self, Vec<N>) {
+        let nodes: Vec<N> = graph.nodes().collect();
+        let ids: std::collections::HashMap<N, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+
+        let mut adj = Self::new(nodes.len());
+        for (a, b, _) in graph.all_edges() {
+            adj.add_edge(ids[&a], ids[&b]);
+        }
+
+        (adj, nodes)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.n
+    }
+
+    pub fn add_edge(&mut self, a: usize, b: usize) {
+        if a != b {
+            let words = self.words;
+            set(&mut self.rows[a * words..(a + 1) * words], b);
+            set(&mut self.rows[b * words..(b + 1) * words], a);
+        }
+    }
+
+    pub fn row(&self, a: usize) -> &[u64] {
+        &self.rows[a * self.words..(a + 1) * self.words]
+    }
+
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.row(a)[b / 64] & (1 << (b % 64)) != 0
+    }
+
+    pub fn degree(&self, a: usize) -> usize {
+        count(self.row(a))
+    }
+
+    /// Repeatedly removes a node of least remaining degree.
+    pub fn degeneracy_order(&self) -> Vec<usize> {
+        let mut degree: Vec<usize> = (0..self.n).map(|a| self.degree(a)).collect();
+        let mut removed = vec![false; self.n];
+        let mut order = Vec::with_capacity(self.n);
+
+        for _ in 0..self.n {
+            let v = (0..self.n).filter(|&v| !removed[v]).min_by_key(|&v| degree[v]).unwrap();
+            removed[v] = true;
+            order.push(v);
+            for u in ones(self.row(v)) {
+                degree[u] -= 1;
+            }
+        }
+
+        order
+    }
+
+    /// Calls `report` with every maximal clique, using Tomita pivoting inside a degeneracy
+    /// ordering of the outer loop. `bound` lets the caller stop expanding branches that can't
+    /// grow past a given size.
+    fn for_each_maximal(&self, mut report: impl FnMut(&[usize]), bound: &dyn Fn() -> usize) {
+        let order = self.degeneracy_order();
+        let mut later = vec![u64::MAX; self.words];
+        let mut clique = Vec::new();
+
+        for &v in &order {
+            clear(&mut later, v);
+            let row = self.row(v);
+            let p = and(row, &later);
+            let x: Vec<u64> = row.iter().zip(&later).map(|(r, l)| r & !l).collect();
+
+            clique.push(v);
+            self.expand(&mut clique, p, x, &mut report, bound);
+            clique.pop();
+        }
+    }
+
+    fn expand(&self, clique: &mut Vec<usize>, mut p: Vec<u64>, mut x: Vec<u64>, report: &mut dyn FnMut(&[usize]), bound: &dyn Fn() -> usize) {
+        let p_count = count(&p);
+        if p_count == 0 {
+            if count(&x) == 0 {
+                report(clique);
+            }
+            return;
+        }
+
+        if clique.len() + p_count < bound() {
+            return;
+        }
+
+        let pivot = ones(&p)
+            .chain(ones(&x))
+            .max_by_key(|&u| self.row(u).iter().zip(&p).map(|(r, q)| (r & q).count_ones()).sum::<u32>())
+            .unwrap();
+
+        let candidates: Vec<usize> = ones(&p).filter(|&v| !self.has_edge(pivot, v)).collect();
+        for v in candidates {
+            let row = self.row(v);
+            clique.push(v);
+            self.expand(clique, and(&p, row), and(&x, row), report, bound);
+            clique.pop();
+
+            clear(&mut p, v);
+            set(&mut x, v);
+        }
+    }
+
+    pub fn maximal_cliques(&self) -> Vec<Vec<usize>> {
+        let mut cliques = Vec::new();
+        self.for_each_maximal(|c| cliques.push(c.to_vec()), &|| 0);
+        cliques
+    }
+
+    pub fn maximum_clique(&self) -> Vec<usize> {
+        let best = std::cell::RefCell::new(Vec::new());
+        self.for_each_maximal(
+            |c| {
+                if c.len() > best.borrow().len() {
+                    *best.borrow_mut() = c.to_vec();
+                }
+            },
+            &|| best.borrow().len() + 1,
+        );
+
+        let mut best = best.into_inner();
+        best.sort_unstable();
+        best
+    }
+
+    /// Every clique of exactly `k` nodes with at least one member passing `filter`, each
+    /// listed in increasing id order.
+    pub fn k_cliques(&self, k: usize, filter: impl Fn(usize) -> bool) -> Vec<Vec<usize>> {
+        fn extend(adj: &BitAdjacency, k: usize, clique: &mut Vec<usize>, candidates: &[u64], filter: &dyn Fn(usize) -> bool, out: &mut Vec<Vec<usize>>) {
+            if clique.len() == k {
+                if clique.iter().any(|&v| filter(v)) {
+                    out.push(clique.clone());
+                }
+                return;
+            }
+
+            for v in ones(candidates) {
+                let mut next = and(candidates, adj.row(v));
+                for (i, w) in next.iter_mut().enumerate() {
+                    // Only grow with larger ids so each clique is found once.
+                    let low = (v + 1).saturating_sub(i * 64).min(64);
+                    *w &= if low == 64 { 0 } else { u64::MAX << low };
+                }
+
+                clique.push(v);
+                extend(adj, k, clique, &next, filter, out);
+                clique.pop();
+            }
+        }
+
+        let mut out = Vec::new();
+        if k > 0 {
+            let mut all = vec![u64::MAX; self.words];
+            for v in self.n..self.words * 64 {
+                clear(&mut all, v);
+            }
+            extend(self, k, &mut Vec::new(), &all, &filter, &mut out);
+        }
+        out
+    }
+}
+
+pub fn maximal_cliques<N: NodeTrait, E>(graph: &UnGraphMap<N, E>) -> Vec<Vec<N>> {
+    let (adj, nodes) = BitAdjacency::from_graph_map(graph);
+    adj.maximal_cliques().into_iter().map(|c| c.into_iter().map(|i| nodes[i]).collect()).collect()
+}
+
+pub fn maximum_clique<N: NodeTrait + Ord, E>(graph: &UnGraphMap<N, E>) -> Vec<N> {
+    let (adj, nodes) = BitAdjacency::from_graph_map(graph);
+    let mut clique: Vec<N> = adj.maximum_clique().into_iter().map(|i| nodes[i]).collect();
+    clique.sort();
+    clique
+}
+
+pub fn k_cliques<N: NodeTrait, E>(graph: &UnGraphMap<N, E>, k: usize, filter: impl Fn(N) -> bool) -> Vec<Vec<N>> {
+    let (adj, nodes) = BitAdjacency::from_graph_map(graph);
+    adj.k_cliques(k, |i| filter(nodes[i]))
+        .into_iter()
+        .map(|c| c.into_iter().map(|i| nodes[i]).collect())
+        .collect()
+}
+
+#[test]
+fn check_lan_party() {
+    let links = "kh-tc qp-kh de-cg ka-co yn-aq qp-ub cg-tb vc-aq tb-ka wh-tc yn-cg kh-ub ta-co de-co tc-td tb-wq \
+                 wh-td ta-ka td-qp aq-cg wq-ub ub-vc de-ta wq-aq wq-vc wh-yn ka-de kh-ta co-tc wh-qp tb-vc td-yn";
+
+    let graph: UnGraphMap<&str, ()> = UnGraphMap::from_edges(links.split(' ').map(|l| l.split_once('-').unwrap()));
+
+    assert_eq!(k_cliques(&graph, 3, |_| true).len(), 12);
+    assert_eq!(k_cliques(&graph, 3, |n: &str| n.starts_with('t')).len(), 7);
+    assert_eq!(maximum_clique(&graph), ["co", "de", "ka", "ta"]);
+
+    // Compare against checking every subset of the 16 computers.
+    let (adj, _) = BitAdjacency::from_graph_map(&graph);
+    let n = adj.node_count();
+    let is_clique = |m: u32| (0..n).all(|a| m & (1 << a) == 0 || (a + 1..n).all(|b| m & (1 << b) == 0 || adj.has_edge(a, b)));
+    let brute: Vec<u32> = (1..1u32 << n)
+        .filter(|&m| is_clique(m) && (0..n).all(|v| m & (1 << v) != 0 || !is_clique(m | (1 << v))))
+        .collect();
+
+    let mut maximal: Vec<u32> = adj.maximal_cliques().iter().map(|c| c.iter().map(|&v| 1 << v).sum()).collect();
+    maximal.sort_unstable();
+    assert_eq!(maximal, brute);
+    assert_eq!(maximal_cliques(&graph).len(), brute.len());
+}
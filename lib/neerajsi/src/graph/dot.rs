@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::named::NamedGraph;
+
+/// Graphviz attributes shared by every node in a class.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeStyle {
+    pub color: Option<String>,
+    pub fill: Option<String>,
+    pub shape: Option<String>,
+}
+
+impl NodeStyle {
+    pub fn color(color: &str) -> Self {
+        Self { color: Some(color.to_string()), ..Default::default() }
+    }
+
+    pub fn filled(fill: &str) -> Self {
+        Self { fill: Some(fill.to_string()), ..Default::default() }
+    }
+
+    pub fn shape(shape: &str) -> Self {
+        Self { shape: Some(shape.to_string()), ..Default::default() }
+    }
+
+    pub fn with_shape(mut self, shape: &str) -> Self {
+        self.shape = Some(shape.to_string());
+        self
+    }
+
+    fn attributes(&self) -> Vec<(&'static str, &str)> {
+        let mut attrs = Vec::new();
+        if let Some(c) = &self.color {
+            attrs.push(("color", c.as_str()));
+        }
+        if let Some(f) = &self.fill {
+            attrs.push(("style", "filled"));
+            attrs.push(("fillcolor", f.as_str()));
+        }
+        if let Some(s) = &self.shape {
+            attrs.push(("shape", s.as_str()));
+        }
+        attrs
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DotNode {
+    id: String,
+    label: Option<String>,
+    class: Option<String>,
+}
+
+/// Collects nodes, edges, style classes and clusters, then writes them as a `.dot` file.
+#[derive(Debug, Clone)]
+pub struct DotWriter {
+    name: String,
+    directed: bool,
+    nodes: Vec<DotNode>,
+    node_index: HashMap<String, usize>,
+    edges: Vec<(String, String, Option<String>)>,
+    classes: BTreeMap<String, NodeStyle>,
+    clusters: Vec<(String, Vec<String>)>,
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl DotWriter {
+    pub fn new(name: &str, directed: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            directed,
+            nodes: Vec::new(),
+            node_index: HashMap::new(),
+            edges: Vec::new(),
+            classes: BTreeMap::new(),
+            clusters: Vec::new(),
+        }
+    }
+
+    pub fn from_named_graph<N, E>(graph: &NamedGraph<N, E>, directed: bool) -> Self
+    where
+        N: Clone + Eq + Hash + Display,
+    {
+        let mut dot = Self::new("graph", directed);
+        for id in 0..graph.node_count() {
+            dot.node(&graph.name(id).to_string());
+        }
+
+        for &(a, b, _) in graph.edges() {
+            dot.edge(&graph.name(a).to_string(), &graph.name(b).to_string());
+        }
+
+        dot
+    }
+
+    fn node_mut(&mut self, id: &str) -> &mut DotNode {
+        let index = match self.node_index.get(id) {
+            Some(&i) => i,
+            None => {
+                self.node_index.insert(id.to_string(), self.nodes.len());
+                self.nodes.push(DotNode { id: id.to_string(), label: None, class: None });
+                self.nodes.len() - 1
+            }
+        };
+        &mut self.nodes[index]
+    }
+
+    pub fn node(&mut self, id: &str) -> &mut Self {
+        self.node_mut(id);
+        self
+    }
+
+    pub fn set_label(&mut self, id: &str, label: &str) -> &mut Self {
+        self.node_mut(id).label = Some(label.to_string());
+        self
+    }
+
+    pub fn define_class(&mut self, class: &str, style: NodeStyle) -> &mut Self {
+        self.classes.insert(class.to_string(), style);
+        self
+    }
+
+    pub fn set_class(&mut self, id: &str, class: &str) -> &mut Self {
+        self.node_mut(id).class = Some(class.to_string());
+        self
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str) -> &mut Self {
+        self.node_mut(from);
+        self.node_mut(to);
+        self.edges.push((from.to_string(), to.to_string(), None));
+        self
+    }
+
+    pub fn labeled_edge(&mut self, from: &str, to: &str, label: &str) -> &mut Self {
+        self.edge(from, to);
+        self.edges.last_mut().unwrap().2 = Some(label.to_string());
+        self
+    }
+
+    /// Draws `nodes` inside a boxed `subgraph cluster_*`.
+    pub fn cluster<'a>(&mut self, label: &str, nodes: impl IntoIterator<Item = &'a str>) -> &mut Self {
+        let nodes: Vec<String> = nodes.into_iter().map(|n| n.to_string()).collect();
+        for n in &nodes {
+            self.node_mut(n);
+        }
+        self.clusters.push((label.to_string(), nodes));
+        self
+    }
+
+    /// Adds a logic gate as its own box node between its input wires and output wire.
+    pub fn gate(&mut self, op: &str, inputs: &[&str], output: &str) -> &mut Self {
+        let gate = format!("{op} -> {output}");
+        self.set_label(&gate, op);
+        self.set_class(&gate, "gate");
+        self.classes.entry("gate".to_string()).or_insert_with(|| NodeStyle::shape("box"));
+
+        for input in inputs {
+            self.edge(input, &gate);
+        }
+        self.edge(&gate, output)
+    }
+
+    fn write_node(&self, w: &mut impl Write, indent: &str, node: &DotNode) -> io::Result<()> {
+        let mut attrs: Vec<(&str, &str)> = Vec::new();
+        if let Some(label) = &node.label {
+            attrs.push(("label", label));
+        }
+        if let Some(style) = node.class.as_ref().and_then(|c| self.classes.get(c)) {
+            attrs.extend(style.attributes());
+        }
+
+        write!(w, "{indent}{}", quote(&node.id))?;
+        if !attrs.is_empty() {
+            let attrs: Vec<String> = attrs.iter().map(|(k, v)| format!("{k}={}", quote(v))).collect();
+            write!(w, " [{}]", attrs.join(", "))?;
+        }
+        writeln!(w, ";")
+    }
+
+    pub fn write(&self, mut w: impl Write) -> io::Result<()> {
+        let (kind, arrow) = if self.directed { ("digraph", "->") } else { ("graph", "--") };
+        writeln!(w, "{kind} {} {{", quote(&self.name))?;
+
+        let mut in_cluster = vec![false; self.nodes.len()];
+        for (i, (label, members)) in self.clusters.iter().enumerate() {
+            writeln!(w, "    subgraph \"cluster_{i}\" {{")?;
+            writeln!(w, "        label={};", quote(label))?;
+            for m in members {
+                let index = self.node_index[m];
+                in_cluster[index] = true;
+                self.write_node(&mut w, "        ", &self.nodes[index])?;
+            }
+            writeln!(w, "    }}")?;
+        }
+
+        for (node, _) in self.nodes.iter().zip(&in_cluster).filter(|(_, &c)| !c) {
+            self.write_node(&mut w, "    ", node)?;
+        }
+
+        for (from, to, label) in &self.edges {
+            write!(w, "    {} {arrow} {}", quote(from), quote(to))?;
+            if let Some(label) = label {
+                write!(w, " [label={}]", quote(label))?;
+            }
+            writeln!(w, ";")?;
+        }
+
+        writeln!(w, "}}")
+    }
+
+    pub fn to_dot_string(&self) -> String {
+        let mut out = Vec::new();
+        self.write(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write(&mut w)?;
+        w.flush()
+    }
+}
+
+#[test]
+fn check_dot_output() {
+    let mut adder = DotWriter::new("adder", true);
+    adder
+        .gate("XOR", &["x00", "y00"], "z00")
+        .gate("AND", &["x00", "y00"], "c00")
+        .define_class("suspect", NodeStyle::filled("red"))
+        .set_class("z00", "suspect")
+        .cluster("inputs", ["x00", "y00"]);
+
+    let dot = adder.to_dot_string();
+    assert!(dot.starts_with("digraph \"adder\" {\n"));
+    assert!(dot.contains("    subgraph \"cluster_0\" {\n        label=\"inputs\";\n        \"x00\";\n"));
+    assert!(dot.contains("\"XOR -> z00\" [label=\"XOR\", shape=\"box\"];"));
+    assert!(dot.contains("\"z00\" [style=\"filled\", fillcolor=\"red\"];"));
+    assert!(dot.contains("\"x00\" -> \"XOR -> z00\";"));
+    assert_eq!(dot.matches("\"x00\";").count(), 1);
+
+    let mut graph = NamedGraph::new();
+    graph.add_edge("you", "out", ());
+    let dot = DotWriter::from_named_graph(&graph, false).to_dot_string();
+    assert!(dot.contains("\"you\" -- \"out\";"));
+}
@@ -0,0 +1,288 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use thiserror::Error;
+
+pub mod cliques;
+pub mod dot;
+pub mod named;
+pub mod precedence;
+
+/// Out-edges by dense node id.
+pub trait Adjacency {
+    fn node_count(&self) -> usize;
+    fn neighbors(&self, node: usize) -> &[usize];
+}
+
+impl<V: AsRef<[usize]>> Adjacency for [V] {
+    fn node_count(&self) -> usize {
+        self.len()
+    }
+
+    fn neighbors(&self, node: usize) -> &[usize] {
+        self[node].as_ref()
+    }
+}
+
+impl<V: AsRef<[usize]>> Adjacency for Vec<V> {
+    fn node_count(&self) -> usize {
+        self.len()
+    }
+
+    fn neighbors(&self, node: usize) -> &[usize] {
+        self[node].as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GraphError {
+    #[error("graph has a cycle through nodes {0:?}")]
+    Cycle(Vec<usize>),
+    #[error("too many waypoints: {0}")]
+    TooManyWaypoints(usize),
+    #[error("path count overflows a u128")]
+    Overflow,
+}
+
+pub fn reverse_edges<G: Adjacency + ?Sized>(graph: &G) -> Vec<Vec<usize>> {
+    let mut reversed = vec![Vec::new(); graph.node_count()];
+    for n in 0..graph.node_count() {
+        for &m in graph.neighbors(n) {
+            reversed[m].push(n);
+        }
+    }
+
+    reversed
+}
+
+pub fn reachable_from<G: Adjacency + ?Sized>(graph: &G, sources: impl IntoIterator<Item = usize>) -> Vec<bool> {
+    let mut seen = vec![false; graph.node_count()];
+    let mut stack = Vec::new();
+    for s in sources {
+        if !std::mem::replace(&mut seen[s], true) {
+            stack.push(s);
+        }
+    }
+
+    while let Some(n) = stack.pop() {
+        for &m in graph.neighbors(n) {
+            if !std::mem::replace(&mut seen[m], true) {
+                stack.push(m);
+            }
+        }
+    }
+
+    seen
+}
+
+/// Topological order of the nodes reachable from `sources` for which `keep` holds, or the
+/// nodes of a cycle among them.
+pub fn topological_order_from<G: Adjacency + ?Sized>(
+    graph: &G,
+    sources: impl IntoIterator<Item = usize>,
+    keep: impl Fn(usize) -> bool,
+) -> Result<Vec<usize>, GraphError> {
+    const WHITE: u8 = 0;
+    const GRAY: u8 = 1;
+    const BLACK: u8 = 2;
+
+    let mut color = vec![WHITE; graph.node_count()];
+    let mut post_order = Vec::new();
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+
+    for s in sources {
+        if color[s] != WHITE || !keep(s) {
+            continue;
+        }
+
+        color[s] = GRAY;
+        stack.push((s, 0));
+
+        while let Some(&(n, i)) = stack.last() {
+            let Some(&m) = graph.neighbors(n).get(i) else {
+                color[n] = BLACK;
+                post_order.push(n);
+                stack.pop();
+                continue;
+            };
+
+            stack.last_mut().unwrap().1 += 1;
+            if !keep(m) {
+                continue;
+            }
+
+            match color[m] {
+                WHITE => {
+                    color[m] = GRAY;
+                    stack.push((m, 0));
+                }
+                GRAY => {
+                    let start = stack.iter().position(|&(v, _)| v == m).unwrap();
+                    return Err(GraphError::Cycle(stack[start..].iter().map(|&(v, _)| v).collect()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    post_order.reverse();
+    Ok(post_order)
+}
+
+pub fn count_paths<G: Adjacency + ?Sized>(graph: &G, source: usize, sink: usize) -> Result<u128, GraphError> {
+    count_paths_via(graph, source, sink, &[])
+}
+
+/// Most waypoints `count_paths_via` accepts. Each node keeps a count per subset of them, so
+/// the table is `node_count * 2^waypoints` u128s, 16 KiB per node at this limit.
+pub const MAX_WAYPOINTS: usize = 10;
+
+/// Counts paths from `source` to `sink` that pass through every one of `waypoints`, in any
+/// order. Only the part of the graph lying between `source` and `sink` has to be acyclic.
+pub fn count_paths_via<G: Adjacency + ?Sized>(
+    graph: &G,
+    source: usize,
+    sink: usize,
+    waypoints: &[usize],
+) -> Result<u128, GraphError> {
+    if waypoints.len() > MAX_WAYPOINTS {
+        return Err(GraphError::TooManyWaypoints(waypoints.len()));
+    }
+
+    let reaches_sink = reachable_from(&reverse_edges(graph), [sink]);
+    let order = topological_order_from(graph, [source], |n| reaches_sink[n])?;
+
+    let masks = 1usize << waypoints.len();
+    let full = masks - 1;
+    let mut bit = vec![0usize; graph.node_count()];
+    for (i, &w) in waypoints.iter().enumerate() {
+        bit[w] |= 1 << i;
+    }
+
+    let mut ways = vec![0u128; graph.node_count() * masks];
+    if !order.is_empty() {
+        ways[source * masks + bit[source]] = 1;
+    }
+
+    for &n in &order {
+        for &m in graph.neighbors(n) {
+            if !reaches_sink[m] {
+                continue;
+            }
+
+            for mask in 0..masks {
+                let count = ways[n * masks + mask];
+                if count != 0 {
+                    let slot = &mut ways[m * masks + (mask | bit[m])];
+                    *slot = slot.checked_add(count).ok_or(GraphError::Overflow)?;
+                }
+            }
+        }
+    }
+
+    Ok(ways[sink * masks + full])
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopoOrder {
+    pub order: Vec<usize>,
+    /// No other order of these nodes satisfies the edges.
+    pub unique: bool,
+}
+
+/// Sorts `nodes` by the edges of `graph` that run between them, breaking ties by lowest id.
+pub fn toposort<G: Adjacency + ?Sized>(graph: &G, nodes: &[usize]) -> Result<TopoOrder, GraphError> {
+    let mut in_subset = vec![false; graph.node_count()];
+    for &n in nodes {
+        in_subset[n] = true;
+    }
+
+    let mut in_degree = vec![0usize; graph.node_count()];
+    for &n in nodes {
+        for &m in graph.neighbors(n) {
+            if in_subset[m] {
+                in_degree[m] += 1;
+            }
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = nodes.iter().filter(|&&n| in_degree[n] == 0).map(|&n| Reverse(n)).collect();
+    let mut order = Vec::with_capacity(nodes.len());
+    let mut unique = true;
+
+    while let Some(Reverse(n)) = ready.pop() {
+        unique &= ready.is_empty();
+        order.push(n);
+        for &m in graph.neighbors(n) {
+            if in_subset[m] {
+                in_degree[m] -= 1;
+                if in_degree[m] == 0 {
+                    ready.push(Reverse(m));
+                }
+            }
+        }
+    }
+
+    if order.len() < nodes.len() {
+        let stuck: Vec<usize> = nodes.iter().copied().filter(|&n| in_degree[n] > 0).collect();
+        let cycle = topological_order_from(graph, stuck, |n| in_subset[n] && in_degree[n] > 0);
+        return Err(cycle.expect_err("nodes left over without a cycle"));
+    }
+
+    Ok(TopoOrder { order, unique })
+}
+
+#[test]
+fn check_path_counts() {
+    use std::collections::HashMap;
+
+    let devices = "svr: aaa bbb\naaa: fft\nfft: ccc\nbbb: tty\ntty: ccc\nccc: ddd eee\nddd: hub\n\
+                   hub: fff\neee: dac\ndac: fff\nfff: ggg hhh\nggg: out\nhhh: out";
+
+    let edge_list: Vec<(&str, &str)> = devices
+        .lines()
+        .flat_map(|line| {
+            let (from, to) = line.split_once(": ").unwrap();
+            to.split_ascii_whitespace().map(move |t| (from, t))
+        })
+        .collect();
+
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    for &(from, to) in &edge_list {
+        for name in [from, to] {
+            let next = ids.len();
+            ids.entry(name).or_insert(next);
+        }
+    }
+
+    let mut edges = vec![Vec::new(); ids.len()];
+    for (from, to) in edge_list {
+        edges[ids[from]].push(ids[to]);
+    }
+
+    let [svr, out, fft, dac, ccc] = ["svr", "out", "fft", "dac", "ccc"].map(|n| ids[n]);
+    assert_eq!(count_paths(&edges, svr, out), Ok(8));
+    assert_eq!(count_paths_via(&edges, svr, out, &[dac, fft]), Ok(2));
+
+    // A loop off to the side of the paths doesn't matter, one on them does.
+    edges[out].push(out);
+    assert_eq!(count_paths(&edges, svr, ccc), Ok(2));
+    edges[out].pop();
+
+    edges[fft].push(ids["aaa"]);
+    assert_eq!(count_paths(&edges, svr, out), Err(GraphError::Cycle(vec![ids["aaa"], fft])));
+
+    // Stacked diamonds double the count each time: 2^127 fits, 2^128 doesn't.
+    let diamonds = |k: usize| {
+        let mut edges = vec![Vec::new(); 3 * k + 1];
+        for i in 0..k {
+            edges[3 * i] = vec![3 * i + 1, 3 * i + 2];
+            edges[3 * i + 1] = vec![3 * i + 3];
+            edges[3 * i + 2] = vec![3 * i + 3];
+        }
+        edges
+    };
+    assert_eq!(count_paths(&diamonds(127), 0, 381), Ok(1 << 127));
+    assert_eq!(count_paths(&diamonds(128), 0, 384), Err(GraphError::Overflow));
+    assert_eq!(count_paths_via(&edges, svr, out, &[0; 11]), Err(GraphError::TooManyWaypoints(11)));
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use petgraph::graph::{DiGraph, NodeIndex, UnGraph};
+
+use super::Adjacency;
+
+/// Adjacency in compressed sparse row form: the targets of node `n` are
+/// `targets[offsets[n]..offsets[n + 1]]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Csr {
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    edge_ids: Vec<usize>,
+}
+
+impl Csr {
+    fn build(node_count: usize, edges: impl Iterator<Item = (usize, usize)> + Clone) -> Self {
+        let mut offsets = vec![0; node_count + 1];
+        for (from, _) in edges.clone() {
+            offsets[from + 1] += 1;
+        }
+
+        for n in 0..node_count {
+            offsets[n + 1] += offsets[n];
+        }
+
+        let mut fill = offsets.clone();
+        let mut targets = vec![0; offsets[node_count]];
+        let mut edge_ids = vec![0; offsets[node_count]];
+        for (id, (from, to)) in edges.enumerate() {
+            targets[fill[from]] = to;
+            edge_ids[fill[from]] = id;
+            fill[from] += 1;
+        }
+
+        Self { offsets, targets, edge_ids }
+    }
+
+    /// Indices into the owning graph's edge list, parallel to `neighbors(node)`.
+    pub fn edge_ids(&self, node: usize) -> &[usize] {
+        &self.edge_ids[self.offsets[node]..self.offsets[node + 1]]
+    }
+}
+
+impl Adjacency for Csr {
+    fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn neighbors(&self, node: usize) -> &[usize] {
+        &self.targets[self.offsets[node]..self.offsets[node + 1]]
+    }
+}
+
+/// A directed graph whose nodes are named by `N` but stored as dense `usize` ids, so the
+/// algorithms in this module can run on integers while output still shows names.
+#[derive(Debug, Clone)]
+pub struct NamedGraph<N, E = ()> {
+    ids: HashMap<N, usize>,
+    names: Vec<N>,
+    edges: Vec<(usize, usize, E)>,
+}
+
+impl<N, E> Default for NamedGraph<N, E> {
+    fn default() -> Self {
+        Self { ids: HashMap::new(), names: Vec::new(), edges: Vec::new() }
+    }
+}
+
+impl<N: Clone + Eq + Hash, E> NamedGraph<N, E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, name: N) -> usize {
+        if let Some(&id) = self.ids.get(&name) {
+            return id;
+        }
+
+        let id = self.names.len();
+        self.ids.insert(name.clone(), id);
+        self.names.push(name);
+        id
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N, weight: E) -> (usize, usize) {
+        let (from, to) = (self.add_node(from), self.add_node(to));
+        self.edges.push((from, to, weight));
+        (from, to)
+    }
+
+    pub fn id(&self, name: &N) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &N {
+        &self.names[id]
+    }
+
+    pub fn names_of<'a>(&'a self, ids: &'a [usize]) -> impl Iterator<Item = &'a N> + 'a {
+        ids.iter().map(|&id| &self.names[id])
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn edges(&self) -> &[(usize, usize, E)] {
+        &self.edges
+    }
+
+    pub fn csr(&self) -> Csr {
+        Csr::build(self.node_count(), self.edges.iter().map(|&(a, b, _)| (a, b)))
+    }
+
+    pub fn reverse_csr(&self) -> Csr {
+        Csr::build(self.node_count(), self.edges.iter().map(|&(a, b, _)| (b, a)))
+    }
+
+    /// Node and edge indices in the result match the ids here.
+    pub fn to_digraph(&self) -> DiGraph<N, E>
+    where
+        E: Clone,
+    {
+        let mut graph = DiGraph::with_capacity(self.node_count(), self.edge_count());
+        for name in &self.names {
+            graph.add_node(name.clone());
+        }
+
+        for (a, b, w) in &self.edges {
+            graph.add_edge(NodeIndex::new(*a), NodeIndex::new(*b), w.clone());
+        }
+
+        graph
+    }
+
+    pub fn to_ungraph(&self) -> UnGraph<N, E>
+    where
+        E: Clone,
+    {
+        let mut graph = UnGraph::with_capacity(self.node_count(), self.edge_count());
+        for name in &self.names {
+            graph.add_node(name.clone());
+        }
+
+        for (a, b, w) in &self.edges {
+            graph.add_edge(NodeIndex::new(*a), NodeIndex::new(*b), w.clone());
+        }
+
+        graph
+    }
+}
+
+impl<'a> NamedGraph<&'a str> {
+    /// Parses `name: target target ...` lines, as in 2025 day11.
+    pub fn from_adjacency_lines(input: &'a str) -> Self {
+        let mut graph = Self::new();
+        for line in input.lines().filter(|l| !l.trim().is_empty()) {
+            let (from, to) = line.split_once(':').unwrap();
+            let from = graph.add_node(from.trim());
+            for t in to.split_ascii_whitespace() {
+                let t = graph.add_node(t);
+                graph.edges.push((from, t, ()));
+            }
+        }
+
+        graph
+    }
+}
+
+#[test]
+fn check_named_graph() {
+    use super::{count_paths, GraphError};
+
+    let devices = "aaa: you hhh\nyou: bbb ccc\nbbb: ddd eee\nccc: ddd eee fff\nddd: ggg\neee: out\n\
+                   fff: out\nggg: out\nhhh: ccc fff iii\niii: out\n";
+
+    let mut graph = NamedGraph::from_adjacency_lines(devices);
+    let (you, out) = (graph.id(&"you").unwrap(), graph.id(&"out").unwrap());
+    assert_eq!(count_paths(&graph.csr(), you, out), Ok(5));
+
+    let reverse = graph.reverse_csr();
+    let mut into_out: Vec<&str> = graph.names_of(reverse.neighbors(out)).copied().collect();
+    into_out.sort();
+    assert_eq!(into_out, ["eee", "fff", "ggg", "iii"]);
+
+    let digraph = graph.to_digraph();
+    assert_eq!((digraph.node_count(), digraph.edge_count()), (graph.node_count(), graph.edge_count()));
+    assert_eq!(digraph[NodeIndex::new(you)], "you");
+
+    graph.add_edge("out", "you", ());
+    let Err(GraphError::Cycle(cycle)) = count_paths(&graph.csr(), you, out) else {
+        panic!("expected a cycle");
+    };
+    assert!(graph.names_of(&cycle).any(|&n| n == "out"));
+}
@@ -0,0 +1,135 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use thiserror::Error;
+
+use super::{toposort, GraphError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum OrderError<N: Debug> {
+    #[error("{later:?} must come before {earlier:?}")]
+    Violation { earlier: N, later: N },
+    #[error("precedence rules form a cycle through {0:?}")]
+    Cycle(Vec<N>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortedOrder<N> {
+    pub order: Vec<N>,
+    pub unique: bool,
+}
+
+/// "`a` must come before `b`" rules over arbitrary keys, e.g. page numbers.
+#[derive(Debug, Clone)]
+pub struct PrecedenceRules<N> {
+    ids: HashMap<N, usize>,
+    keys: Vec<N>,
+    after: Vec<Vec<usize>>,
+    rules: HashSet<(usize, usize)>,
+}
+
+impl<N> Default for PrecedenceRules<N> {
+    fn default() -> Self {
+        Self { ids: HashMap::new(), keys: Vec::new(), after: Vec::new(), rules: HashSet::new() }
+    }
+}
+
+impl<N: Copy + Eq + Hash + Debug> PrecedenceRules<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn intern(&mut self, key: N) -> usize {
+        if let Some(&id) = self.ids.get(&key) {
+            return id;
+        }
+
+        let id = self.keys.len();
+        self.ids.insert(key, id);
+        self.keys.push(key);
+        self.after.push(Vec::new());
+        id
+    }
+
+    pub fn add(&mut self, before: N, after: N) {
+        let (a, b) = (self.intern(before), self.intern(after));
+        if self.rules.insert((a, b)) {
+            self.after[a].push(b);
+        }
+    }
+
+    pub fn must_precede(&self, a: N, b: N) -> bool {
+        match (self.ids.get(&a), self.ids.get(&b)) {
+            (Some(&a), Some(&b)) => self.rules.contains(&(a, b)),
+            _ => false,
+        }
+    }
+
+    /// The first pair in `sequence` that breaks a rule.
+    pub fn check_order(&self, sequence: &[N]) -> Result<(), OrderError<N>> {
+        for (i, &earlier) in sequence.iter().enumerate() {
+            if let Some(&later) = sequence[i + 1..].iter().find(|&&later| self.must_precede(later, earlier)) {
+                return Err(OrderError::Violation { earlier, later });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Orders `items` by the rules that apply between them. Items no rule mentions keep
+    /// their relative order at the front.
+    pub fn sort_subset(&self, items: &[N]) -> Result<SortedOrder<N>, OrderError<N>> {
+        let (known, unknown): (Vec<N>, Vec<N>) = items.iter().partition(|k| self.ids.contains_key(k));
+        let nodes: Vec<usize> = known.iter().map(|k| self.ids[k]).collect();
+
+        let sorted = toposort(&self.after, &nodes).map_err(|e| match e {
+            GraphError::Cycle(c) => OrderError::Cycle(c.into_iter().map(|n| self.keys[n]).collect()),
+            other => unreachable!("{other}"),
+        })?;
+
+        let unique = sorted.unique && (unknown.is_empty() || items.len() <= 1);
+        let order = unknown.into_iter().chain(sorted.order.into_iter().map(|n| self.keys[n])).collect();
+        Ok(SortedOrder { order, unique })
+    }
+}
+
+impl<N: Copy + Eq + Hash + Debug> FromIterator<(N, N)> for PrecedenceRules<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(iter: I) -> Self {
+        let mut rules = Self::new();
+        for (a, b) in iter {
+            rules.add(a, b);
+        }
+        rules
+    }
+}
+
+#[test]
+fn check_page_ordering() {
+    let rules: PrecedenceRules<u32> = [
+        (47, 53), (97, 13), (97, 61), (97, 47), (75, 29), (61, 13), (75, 53), (29, 13), (97, 29), (53, 29), (61, 53),
+        (97, 53), (61, 29), (47, 13), (75, 47), (97, 75), (47, 61), (75, 61), (47, 29), (75, 13), (53, 13),
+    ].into_iter().collect();
+
+    let updates: [&[u32]; 6] = [
+        &[75, 47, 61, 53, 29],
+        &[97, 61, 53, 29, 13],
+        &[75, 29, 13],
+        &[75, 97, 47, 61, 53],
+        &[61, 13, 29],
+        &[97, 13, 75, 29, 47],
+    ];
+
+    let middle = |u: &[u32]| u[u.len() / 2];
+    let (good, bad): (Vec<&[u32]>, Vec<&[u32]>) = updates.iter().partition(|u| rules.check_order(u).is_ok());
+    assert_eq!(good.iter().map(|u| middle(u)).sum::<u32>(), 143);
+    assert_eq!(rules.check_order(&[61, 13, 29]), Err(OrderError::Violation { earlier: 13, later: 29 }));
+
+    let fixed: Vec<SortedOrder<u32>> = bad.iter().map(|u| rules.sort_subset(u).unwrap()).collect();
+    assert!(fixed.iter().all(|f| f.unique));
+    assert_eq!(fixed.iter().map(|f| middle(&f.order)).sum::<u32>(), 123);
+
+    let mut cyclic = rules.clone();
+    cyclic.add(13, 97);
+    assert!(matches!(cyclic.sort_subset(&[97, 13, 47]), Err(OrderError::Cycle(_))));
+}
@@ -0,0 +1,295 @@
+use itertools::Itertools;
+use num_rational::Ratio;
+use num_traits::{Signed, Zero};
+use thiserror::Error;
+
+use crate::linalg::LinearSystem;
+use crate::numtheory::{gcd_of, lcm_of};
+
+type Q = Ratio<i128>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IlpError {
+    #[error("no non-negative integer solution exists")]
+    Infeasible,
+    /// Some feasible direction that raises this variable keeps lowering the cost.
+    #[error("the cost decreases without limit as variable {0} grows")]
+    Unbounded(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IlpSolution {
+    pub x: Vec<i64>,
+    pub cost: i64,
+}
+
+/// Upper bounds from rows whose coefficients all share a sign: each variable in such a row
+/// can be at most `b / a`. Such a row whose right-hand side has the other sign can't be met.
+fn upper_bounds<S: AsRef<[i64]>>(a: &[S], b: &[i64], cols: usize) -> Result<Vec<Option<i64>>, IlpError> {
+    let mut bounds = vec![None; cols];
+
+    for (row, &rhs) in a.iter().zip(b) {
+        let row = row.as_ref();
+        let sign = if row.iter().all(|&v| v >= 0) {
+            1
+        } else if row.iter().all(|&v| v <= 0) {
+            -1
+        } else {
+            continue;
+        };
+
+        if sign * rhs < 0 {
+            return Err(IlpError::Infeasible);
+        }
+
+        for (j, &v) in row.iter().enumerate().filter(|(_, &v)| v != 0) {
+            let limit = (sign * rhs) / (sign * v);
+            bounds[j] = Some(bounds[j].map_or(limit, |b: i64| b.min(limit)));
+        }
+    }
+
+    Ok(bounds)
+}
+
+/// By Meyer's theorem every integer solution of `A x = b, x >= 0` is one bounded by the
+/// returned limits plus non-negative integer multiples of the returned rays, the extreme
+/// rays of `{d >= 0 : A d = 0}` scaled to primitive integer vectors. The limits come from
+/// the largest vertex coordinates plus the rays themselves. Vertices and rays are found by
+/// trying every column subset that could be a basis or a ray's support, which is
+/// exponential in the number of columns. `None` if there is no vertex, so no solution.
+fn decompose(system: &LinearSystem<Q>, cols: usize) -> Option<(Vec<i64>, Vec<Vec<i128>>)> {
+    let mut rows: Vec<&[Q]> = system.reduced[..system.rank].iter().map(|r| r.as_slice()).collect();
+    let zero_row = vec![Q::zero(); cols + 1];
+    if rows.is_empty() {
+        rows.push(&zero_row);
+    }
+    let restrict = |support: &[usize], homogeneous: bool| {
+        let sub: Vec<Vec<Q>> = rows.iter().map(|r| support.iter().map(|&j| r[j]).collect()).collect();
+        let rhs: Vec<Q> = rows.iter().map(|r| if homogeneous { Q::zero() } else { r[cols] }).collect();
+        LinearSystem::solve(&sub, &rhs)
+    };
+
+    let mut highest: Option<Vec<Q>> = None;
+    for support in (0..cols).combinations(system.rank) {
+        let basis = restrict(&support, false);
+        let Some(x) = basis.particular.filter(|_| basis.rank == system.rank) else { continue };
+        if x.iter().any(|v| v.is_negative()) {
+            continue;
+        }
+        let highest = highest.get_or_insert_with(|| vec![Q::zero(); cols]);
+        for (&j, &v) in support.iter().zip(&x) {
+            highest[j] = highest[j].max(v);
+        }
+    }
+
+    // A null vector of a support is a ray when it is unique, positive, and uses every column.
+    let mut rays = Vec::new();
+    for size in 1..=cols.min(system.rank + 1) {
+        for support in (0..cols).combinations(size) {
+            let kernel = restrict(&support, true);
+            let [d] = kernel.null_space.as_slice() else { continue };
+            if !(d.iter().all(|v| v.is_positive()) || d.iter().all(|v| v.is_negative())) {
+                continue;
+            }
+
+            let scale = Q::from(lcm_of(d.iter().map(|v| *v.denom()))? as i128);
+            let scaled: Vec<i128> = d.iter().map(|v| (v * scale).to_integer().abs()).collect();
+            let divisor = gcd_of(scaled.iter().copied()) as i128;
+            let mut ray = vec![0; cols];
+            for (&j, v) in support.iter().zip(scaled) {
+                ray[j] = v / divisor;
+            }
+            rays.push(ray);
+        }
+    }
+
+    let limits = highest?
+        .iter()
+        .enumerate()
+        .map(|(j, v)| {
+            let reach = rays.iter().fold(v.floor().to_integer(), |sum: i128, r| sum.saturating_add(r[j]));
+            reach.try_into().unwrap_or(i64::MAX)
+        })
+        .collect();
+    Some((limits, rays))
+}
+
+struct Search<'a> {
+    system: &'a LinearSystem<Q>,
+    free: Vec<usize>,
+    bounds: Vec<i64>,
+    cost: &'a [i64],
+    // Cost as `base + reduced · free`, with the pivot variables substituted out.
+    base: Q,
+    reduced: Vec<Q>,
+    best: Option<(Q, Vec<Q>)>,
+}
+
+impl Search<'_> {
+    /// The bound on `x_f`, tightened by the best cost so far when raising `x_f` costs something:
+    /// every other variable can take off at most its negative cost times its bound.
+    fn limit(&self, f: usize) -> i64 {
+        let (Some((best, _)), true) = (&self.best, self.cost[f] > 0) else {
+            return self.bounds[f];
+        };
+
+        let savings = (0..self.bounds.len())
+            .filter(|&k| k != f && self.cost[k] < 0)
+            .fold(0i128, |sum, k| sum.saturating_add(self.cost[k] as i128 * self.bounds[k] as i128));
+        let room = best.to_integer().saturating_sub(savings) / self.cost[f] as i128;
+        self.bounds[f].min(room.try_into().unwrap_or(i64::MAX))
+    }
+
+    fn pivot_values(&self, assigned: &[i64]) -> Vec<(Q, Q)> {
+        let cols = self.bounds.len();
+        (0..self.system.rank).map(|i| {
+            let row = &self.system.reduced[i];
+            let (mut lo, mut hi) = (row[cols], row[cols]);
+            for (k, &f) in self.free.iter().enumerate() {
+                let coef = -row[f];
+                match assigned.get(k) {
+                    Some(&v) => {
+                        lo += coef * Q::from(v as i128);
+                        hi += coef * Q::from(v as i128);
+                    }
+                    None if coef.is_positive() => hi += coef * Q::from(self.bounds[f] as i128),
+                    None => lo += coef * Q::from(self.bounds[f] as i128),
+                }
+            }
+            (lo, hi)
+        }).collect()
+    }
+
+    fn run(&mut self, assigned: &mut Vec<i64>) {
+        let ranges = self.pivot_values(assigned);
+        let fits = ranges.iter().zip(&self.system.pivots).all(|((lo, hi), &p)| !hi.is_negative() && *lo <= Q::from(self.bounds[p] as i128));
+        if !fits {
+            return;
+        }
+
+        let mut cost_bound = self.base;
+        for (k, rc) in self.reduced.iter().enumerate() {
+            match assigned.get(k) {
+                Some(&v) => cost_bound += rc * Q::from(v as i128),
+                None if rc.is_negative() => cost_bound += rc * Q::from(self.bounds[self.free[k]] as i128),
+                None => {}
+            }
+        }
+        if self.best.as_ref().is_some_and(|(best, _)| cost_bound >= *best) {
+            return;
+        }
+
+        if assigned.len() == self.free.len() {
+            // Every range has collapsed to a single value.
+            if ranges.iter().all(|(v, _)| v.is_integer()) {
+                let mut x = vec![Q::zero(); self.bounds.len()];
+                for (&p, (v, _)) in self.system.pivots.iter().zip(&ranges) {
+                    x[p] = *v;
+                }
+                for (&f, &v) in self.free.iter().zip(assigned.iter()) {
+                    x[f] = Q::from(v as i128);
+                }
+                self.best = Some((cost_bound, x));
+            }
+            return;
+        }
+
+        let f = self.free[assigned.len()];
+        let mut v = 0;
+        while v <= self.limit(f) {
+            assigned.push(v);
+            self.run(assigned);
+            assigned.pop();
+            v += 1;
+        }
+    }
+}
+
+/// Minimizes `cost · x` subject to `a x = b` over non-negative integer `x`. The system is
+/// reduced to RREF first, so only the free variables are branched on. Rows whose
+/// coefficients share a sign usually bound every variable; when some free variable is left
+/// without a bound, `decompose` supplies one (at exponential cost in the column count) and
+/// its rays show whether the cost can keep falling. Each solution found also caps the
+/// variables with positive cost for the rest of the search.
+pub fn minimize_nonneg_integer<S: AsRef<[i64]>>(a: &[S], b: &[i64], cost: &[i64]) -> Result<IlpSolution, IlpError> {
+    let cols = cost.len();
+    let q = |v: i64| Q::from(v as i128);
+    let rows: Vec<Vec<Q>> = a.iter().map(|r| r.as_ref().iter().map(|&v| q(v)).collect()).collect();
+    let rhs: Vec<Q> = b.iter().map(|&v| q(v)).collect();
+
+    let system = LinearSystem::solve(&rows, &rhs);
+    if system.inconsistent {
+        return Err(IlpError::Infeasible);
+    }
+
+    let row_bounds = upper_bounds(a, b, cols)?;
+    let free = system.free_variables();
+    let mut bounds: Vec<i64> = row_bounds.iter().map(|b| b.unwrap_or(i64::MAX)).collect();
+    let mut improving = None;
+    if free.iter().any(|&f| row_bounds[f].is_none()) {
+        let (limits, rays) = decompose(&system, cols).ok_or(IlpError::Infeasible)?;
+        for (bound, limit) in bounds.iter_mut().zip(limits) {
+            *bound = (*bound).min(limit);
+        }
+
+        // Every ray has a free variable in its support, since the free ones determine the rest.
+        let cheaper = rays.iter().find(|r| r.iter().zip(cost).map(|(d, &c)| d * c as i128).sum::<i128>() < 0);
+        improving = cheaper.map(|r| *free.iter().find(|&&f| r[f] != 0).unwrap());
+    }
+
+    let reduced_cost = |f: usize| q(cost[f]) - system.pivots.iter().enumerate().map(|(i, &p)| q(cost[p]) * system.reduced[i][f]).sum::<Q>();
+    let base = system.pivots.iter().enumerate().map(|(i, &p)| q(cost[p]) * system.reduced[i][cols]).sum();
+    let reduced = free.iter().map(|&f| reduced_cost(f)).collect();
+
+    let mut search = Search { system: &system, free, bounds, cost, base, reduced, best: None };
+    search.run(&mut Vec::new());
+
+    if let (Some(j), Some(_)) = (improving, &search.best) {
+        return Err(IlpError::Unbounded(j));
+    }
+
+    let (_, x) = search.best.ok_or(IlpError::Infeasible)?;
+    let x: Vec<i64> = x.iter().map(|v| v.to_integer() as i64).collect();
+    let cost = x.iter().zip(search.cost).map(|(x, c)| x * c).sum();
+    Ok(IlpSolution { x, cost })
+}
+
+#[test]
+fn check_fewest_presses() {
+    // 2025 day10 part 2: each button adds one to the listed counters.
+    let machines: [(&[&[usize]], &[i64]); 3] = [
+        (&[&[3], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]], &[3, 5, 4, 7]),
+        (&[&[0, 2, 3, 4], &[2, 3], &[0, 4], &[0, 1, 2], &[1, 2, 3, 4]], &[7, 5, 12, 7, 2]),
+        (&[&[0, 1, 2, 3, 4], &[0, 3, 4], &[0, 1, 2, 4, 5], &[1, 2]], &[10, 11, 11, 5, 10, 5]),
+    ];
+
+    let presses: Vec<i64> = machines
+        .iter()
+        .map(|(buttons, joltages)| {
+            let a: Vec<Vec<i64>> = (0..joltages.len()).map(|r| buttons.iter().map(|b| b.contains(&r) as i64).collect()).collect();
+            let solution = minimize_nonneg_integer(&a, joltages, &vec![1; buttons.len()]).unwrap();
+            for (row, &target) in a.iter().zip(*joltages) {
+                assert_eq!(row.iter().zip(&solution.x).map(|(p, q)| p * q).sum::<i64>(), target);
+            }
+            solution.cost
+        })
+        .collect();
+    assert_eq!(presses, [10, 12, 11]);
+
+    // 2x = 3 has a rational solution only.
+    assert_eq!(minimize_nonneg_integer(&[[2]], &[3], &[1]), Err(IlpError::Infeasible));
+    assert_eq!(minimize_nonneg_integer(&[[1, 1]], &[-1], &[1, 1]), Err(IlpError::Infeasible));
+
+    // No row bounds y in these, but the optimum is still found.
+    let solve = |a: &[[i64; 2]], b: &[i64], c: &[i64]| minimize_nonneg_integer(a, b, c).map(|s| (s.x, s.cost));
+    assert_eq!(solve(&[[1, -1]], &[0], &[1, 1]), Ok((vec![0, 0], 0)));
+    assert_eq!(solve(&[[1, -1]], &[-2], &[2, 1]), Ok((vec![0, 2], 2)));
+    assert_eq!(solve(&[[1, -1]], &[0], &[2, -1]), Ok((vec![0, 0], 0)));
+    assert_eq!(solve(&[[3, -2], [0, 0]], &[1, 0], &[1, 1]), Ok((vec![1, 1], 2)));
+    assert_eq!(solve(&[[2, -2]], &[1], &[1, 1]), Err(IlpError::Infeasible));
+    assert_eq!(solve(&[[0, 0]], &[0], &[0, 1]), Ok((vec![0, 0], 0)));
+
+    // Growing y (alone, or along with x = y) keeps lowering the cost.
+    assert_eq!(minimize_nonneg_integer(&[[1, 0]], &[4], &[1, -1]), Err(IlpError::Unbounded(1)));
+    assert_eq!(minimize_nonneg_integer(&[[1, -1]], &[0], &[1, -2]), Err(IlpError::Unbounded(1)));
+}
@@ -0,0 +1,169 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbImage {
+    /// Draws every cell of `map` as a `scale` x `scale` block of `color(cell)`.
+    pub fn from_map<S, T>(map: &[S], scale: usize, color: impl Fn(&T) -> Rgb) -> Self
+    where
+        S: AsRef<[T]>,
+    {
+        let rows = map.len();
+        let cols = map.first().map_or(0, |r| r.as_ref().len());
+        let width = cols * scale;
+        let mut pixels = Vec::with_capacity(width * rows * scale * 3);
+
+        for row in map {
+            let line: Vec<u8> = row
+                .as_ref()
+                .iter()
+                .flat_map(|cell| {
+                    let rgb = color(cell);
+                    std::iter::repeat_n(rgb, scale).flatten()
+                })
+                .collect();
+
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+
+        Self { width, height: rows * scale, pixels }
+    }
+
+    pub fn write_ppm(&self, mut w: impl Write) -> io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        w.write_all(&self.pixels)
+    }
+
+    pub fn write_png(&self, w: impl Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer.write_image_data(&self.pixels).map_err(io::Error::other)
+    }
+
+    pub fn write(&self, w: impl Write, format: ImageFormat) -> io::Result<()> {
+        match format {
+            ImageFormat::Ppm => self.write_ppm(w),
+            ImageFormat::Png => self.write_png(w),
+        }
+    }
+
+    /// Saves to `path`, picking the format from its extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let format = ImageFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("unknown image extension: {}", path.display()))
+        })?;
+
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write(&mut w, format)?;
+        w.flush()
+    }
+}
+
+pub fn save_map<S, T>(path: impl AsRef<Path>, map: &[S], scale: usize, color: impl Fn(&T) -> Rgb) -> io::Result<()>
+where
+    S: AsRef<[T]>,
+{
+    RgbImage::from_map(map, scale, color).save(path)
+}
+
+/// Writes numbered frames (`<prefix>_00000.png`, ...) into a directory so a whole simulation
+/// can be stepped through offline or stitched together with an external tool.
+#[derive(Debug, Clone)]
+pub struct FrameWriter {
+    dir: PathBuf,
+    prefix: String,
+    format: ImageFormat,
+    scale: usize,
+    next_frame: usize,
+}
+
+impl FrameWriter {
+    pub fn new(dir: impl Into<PathBuf>, prefix: &str, format: ImageFormat, scale: usize) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, prefix: prefix.to_string(), format, scale, next_frame: 0 })
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.next_frame
+    }
+
+    pub fn frame_path(&self, frame: usize) -> PathBuf {
+        self.dir.join(format!("{}_{frame:05}.{}", self.prefix, self.format.extension()))
+    }
+
+    pub fn write_image(&mut self, image: &RgbImage) -> io::Result<PathBuf> {
+        let path = self.frame_path(self.next_frame);
+        let mut w = BufWriter::new(File::create(&path)?);
+        image.write(&mut w, self.format)?;
+        w.flush()?;
+
+        self.next_frame += 1;
+        Ok(path)
+    }
+
+    pub fn write_frame<S, T>(&mut self, map: &[S], color: impl Fn(&T) -> Rgb) -> io::Result<PathBuf>
+    where
+        S: AsRef<[T]>,
+    {
+        let image = RgbImage::from_map(map, self.scale, color);
+        self.write_image(&image)
+    }
+}
+
+#[test]
+fn check_image_export() {
+    let map = ["#.", ".#"].map(|l| l.as_bytes());
+    let color = |&t: &u8| if t == b'#' { [255, 255, 255] } else { [0, 0, 0] };
+
+    let image = RgbImage::from_map(&map, 2, color);
+    assert_eq!((image.width, image.height), (4, 4));
+    assert_eq!(&image.pixels[..12], &[255, 255, 255, 255, 255, 255, 0, 0, 0, 0, 0, 0]);
+
+    let mut ppm = Vec::new();
+    image.write_ppm(&mut ppm).unwrap();
+    assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+    assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+
+    let mut png = Vec::new();
+    image.write_png(&mut png).unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+}
@@ -0,0 +1,556 @@
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use num_traits::{NumCast, PrimInt};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IntervalParseError {
+    #[error("expected `start-end`, got {0:?}")]
+    Format(String),
+    #[error("interval {0:?} ends before it starts")]
+    Reversed(String),
+}
+
+/// Parses an inclusive `a-b` range. Either end may be negative, as in `-5--3`.
+pub fn parse_inclusive<T: PrimInt + FromStr>(s: &str) -> Result<RangeInclusive<T>, IntervalParseError> {
+    let s = s.trim();
+    let format = || IntervalParseError::Format(s.to_string());
+
+    // The separator is the first '-' that isn't a leading sign.
+    let split = s.char_indices().skip(1).find(|&(_, c)| c == '-').map(|(i, _)| i).ok_or_else(format)?;
+    let start: T = s[..split].trim().parse().map_err(|_| format())?;
+    let end: T = s[split + 1..].trim().parse().map_err(|_| format())?;
+
+    if end < start {
+        return Err(IntervalParseError::Reversed(s.to_string()));
+    }
+    Ok(start..=end)
+}
+
+fn successor<T: PrimInt>(x: T) -> Option<T> {
+    x.checked_add(&T::one())
+}
+
+fn predecessor<T: PrimInt>(x: T) -> Option<T> {
+    x.checked_sub(&T::one())
+}
+
+fn shift<T: PrimInt>(x: T, offset: i128) -> T {
+    // Every value and result fits in an i128 or, past i128::MAX, in a u128.
+    let shifted = x.to_i128().and_then(|v| v.checked_add(offset)).and_then(<T as NumCast>::from);
+    shifted
+        .or_else(|| x.to_u128()?.checked_add_signed(offset).and_then(<T as NumCast>::from))
+        .expect("shifted value out of range")
+}
+
+/// `to - from`, which must be less than 2^127 either way.
+fn offset_between<T: PrimInt>(from: T, to: T) -> i128 {
+    let offset = match (from.to_i128(), to.to_i128()) {
+        (Some(a), Some(b)) => b.checked_sub(a),
+        // Only u128 values don't fit in an i128.
+        _ => {
+            let (a, b) = (from.to_u128().unwrap(), to.to_u128().unwrap());
+            if b >= a { 0i128.checked_add_unsigned(b - a) } else { 0i128.checked_sub_unsigned(a - b) }
+        }
+    };
+    offset.filter(|&o| o != i128::MIN).expect("offset doesn't fit in an i128")
+}
+
+/// A set of integers stored as sorted, disjoint, non-adjacent inclusive intervals.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<(T, T)>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self { intervals: Vec::new() }
+    }
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges overlapping and touching intervals of a sorted list.
+    fn from_sorted(sorted: impl IntoIterator<Item = (T, T)>) -> Self {
+        let mut intervals: Vec<(T, T)> = Vec::new();
+        for (start, end) in sorted {
+            match intervals.last_mut() {
+                Some(last) if successor(last.1).is_none_or(|next| start <= next) => last.1 = last.1.max(end),
+                _ => intervals.push((start, end)),
+            }
+        }
+        Self { intervals }
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if end < start {
+            return;
+        }
+
+        // Everything touching [start, end] gets absorbed into it.
+        let first = self.intervals.partition_point(|&(_, e)| successor(e).is_some_and(|next| next < start));
+        let last = self.intervals.partition_point(|&(s, _)| predecessor(s).is_none_or(|prev| prev <= end));
+        let merged = self.intervals[first..last]
+            .iter()
+            .fold((start, end), |(s, e), &(a, b)| (s.min(a), e.max(b)));
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        *self = self.difference(&Self::from_iter([range]));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The number of integers covered. Overflows only for the whole range of a 128-bit type.
+    pub fn len(&self) -> u128 {
+        self.intervals
+            .iter()
+            .map(|&(s, e)| {
+                // The true width always fits in a u128, so wrapping in i128 is exact.
+                let width = match (e.to_i128(), s.to_i128()) {
+                    (Some(e), Some(s)) => e.wrapping_sub(s) as u128,
+                    _ => e.to_u128().unwrap() - s.to_u128().unwrap(),
+                };
+                width + 1
+            })
+            .sum()
+    }
+
+    pub fn intervals(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.intervals.iter().map(|&(s, e)| s..=e)
+    }
+
+    pub fn interval_containing(&self, x: T) -> Option<RangeInclusive<T>> {
+        let i = self.intervals.partition_point(|&(_, e)| e < x);
+        self.intervals.get(i).filter(|&&(s, _)| s <= x).map(|&(s, e)| s..=e)
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        self.interval_containing(x).is_some()
+    }
+
+    pub fn contains_range(&self, range: &RangeInclusive<T>) -> bool {
+        self.interval_containing(*range.start()).is_some_and(|r| range.end() <= r.end())
+    }
+
+    pub fn overlaps(&self, range: &RangeInclusive<T>) -> bool {
+        let i = self.intervals.partition_point(|&(_, e)| e < *range.start());
+        self.intervals.get(i).is_some_and(|&(s, _)| s <= *range.end())
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut all: Vec<(T, T)> = Vec::with_capacity(self.intervals.len() + other.intervals.len());
+        let (mut a, mut b) = (self.intervals.iter().peekable(), other.intervals.iter().peekable());
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            all.push(if x.0 <= y.0 { *a.next().unwrap() } else { *b.next().unwrap() });
+        }
+        all.extend(a.chain(b));
+        Self::from_sorted(all)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut out = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let ((a, b), (c, d)) = (self.intervals[i], other.intervals[j]);
+            let (s, e) = (a.max(c), b.min(d));
+            if s <= e {
+                out.push((s, e));
+            }
+            if b < d {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { intervals: out }
+    }
+
+    /// Everything in `self` that isn't in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut out = Vec::new();
+        let mut j = 0;
+        for &(start, end) in &self.intervals {
+            while j < other.intervals.len() && other.intervals[j].1 < start {
+                j += 1;
+            }
+
+            let mut start = Some(start);
+            let mut k = j;
+            while let (Some(s), Some(&(c, d))) = (start, other.intervals.get(k)) {
+                if c > end {
+                    break;
+                }
+                if c > s {
+                    out.push((s, predecessor(c).unwrap()));
+                }
+                start = successor(d).filter(|&n| n <= end);
+                k += 1;
+            }
+
+            if let Some(s) = start {
+                out.push((s, end));
+            }
+        }
+        Self { intervals: out }
+    }
+
+    /// Everything within `bounds` that isn't in `self`.
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> Self {
+        Self::from_iter([bounds]).difference(self)
+    }
+
+    /// Moves every interval by `offset`. Panics if a result doesn't fit in `T`.
+    pub fn shifted(&self, offset: i128) -> Self {
+        Self { intervals: self.intervals.iter().map(|&(s, e)| (shift(s, offset), shift(e, offset))).collect() }
+    }
+
+    /// One inclusive `a-b` range per non-empty line.
+    pub fn parse_lines(input: &str) -> Result<Self, IntervalParseError>
+    where
+        T: FromStr,
+    {
+        input.lines().filter(|l| !l.trim().is_empty()).map(parse_inclusive).collect()
+    }
+}
+
+impl<T: PrimInt> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut ranges: Vec<(T, T)> = iter.into_iter().map(|r| r.into_inner()).filter(|(s, e)| s <= e).collect();
+        ranges.sort_unstable();
+        Self::from_sorted(ranges)
+    }
+}
+
+/// Disjoint inclusive intervals, each carrying a value. Inserting over existing intervals
+/// overwrites the overlapping parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalMap<T, V> {
+    // start -> (end, value)
+    map: BTreeMap<T, (T, V)>,
+}
+
+impl<T, V> Default for IntervalMap<T, V> {
+    fn default() -> Self {
+        Self { map: BTreeMap::new() }
+    }
+}
+
+impl<T: PrimInt, V: Clone + PartialEq> IntervalMap<T, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<T>, value: V) {
+        let (start, end) = range.into_inner();
+        if end < start {
+            return;
+        }
+        self.clear_range(start, end);
+
+        // Join with neighbours holding the same value.
+        let (mut start, mut end) = (start, end);
+        if let Some((&s, (e, v))) = self.map.range(..start).next_back() {
+            if *v == value && successor(*e) == Some(start) {
+                start = s;
+                self.map.remove(&s);
+            }
+        }
+        if let Some(next) = successor(end) {
+            if let Some((e, v)) = self.map.get(&next) {
+                if *v == value {
+                    end = *e;
+                    self.map.remove(&next);
+                }
+            }
+        }
+
+        self.map.insert(start, (end, value));
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if start <= end {
+            self.clear_range(start, end);
+        }
+    }
+
+    fn clear_range(&mut self, start: T, end: T) {
+        // An interval starting before `start` may stick out on one or both sides.
+        if let Some((&s, &(e, ref v))) = self.map.range(..start).next_back() {
+            if e >= start {
+                let v = v.clone();
+                self.map.insert(s, (predecessor(start).unwrap(), v.clone()));
+                if e > end {
+                    self.map.insert(successor(end).unwrap(), (e, v));
+                }
+            }
+        }
+
+        let inside: Vec<T> = self.map.range(start..=end).map(|(&s, _)| s).collect();
+        for s in inside {
+            let (e, v) = self.map.remove(&s).unwrap();
+            if e > end {
+                self.map.insert(successor(end).unwrap(), (e, v));
+            }
+        }
+    }
+
+    pub fn get(&self, x: T) -> Option<&V> {
+        self.get_interval(x).map(|(_, v)| v)
+    }
+
+    pub fn get_interval(&self, x: T) -> Option<(RangeInclusive<T>, &V)> {
+        let (&s, (e, v)) = self.map.range(..=x).next_back()?;
+        (x <= *e).then_some((s..=*e, v))
+    }
+
+    /// Intervals overlapping `range`, clipped to it.
+    pub fn overlapping(&self, range: RangeInclusive<T>) -> impl Iterator<Item = (RangeInclusive<T>, &V)> + '_ {
+        let (start, end) = range.into_inner();
+        let from = self.map.range(..=start).next_back().map_or(start, |(&s, _)| s);
+        self.map
+            .range(from..=end)
+            .filter(move |(_, (e, _))| *e >= start)
+            .map(move |(&s, (e, v))| (s.max(start)..=(*e).min(end), v))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (RangeInclusive<T>, &V)> + '_ {
+        self.map.iter().map(|(&s, (e, v))| (s..=*e, v))
+    }
+
+    pub fn keys(&self) -> IntervalSet<T> {
+        IntervalSet::from_sorted(self.map.iter().map(|(&s, &(e, _))| (s, e)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// A map on integers that shifts each of a set of disjoint source intervals by its own
+/// offset and leaves everything else alone, like one stage of an almanac. Offsets are kept
+/// as `i128`, so with `u128` values no single shift may move a value by 2^127 or more.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseShiftMap<T> {
+    offsets: IntervalMap<T, i128>,
+}
+
+impl<T> Default for PiecewiseShiftMap<T> {
+    fn default() -> Self {
+        Self { offsets: IntervalMap::default() }
+    }
+}
+
+impl<T: PrimInt> PiecewiseShiftMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps `source` onto the interval starting at `destination`, replacing any shift
+    /// already set there.
+    pub fn insert(&mut self, source: RangeInclusive<T>, destination: T) {
+        let offset = offset_between(*source.start(), destination);
+        // Fail here rather than on first use if the destination doesn't fit in `T`.
+        shift(*source.end(), offset);
+        if offset == 0 {
+            self.offsets.remove(source);
+        } else {
+            self.offsets.insert(source, offset);
+        }
+    }
+
+    /// An almanac line: `len` values from `source` onwards map to `destination` onwards.
+    pub fn insert_len(&mut self, destination: T, source: T, len: T) {
+        if len > T::zero() {
+            self.insert(source..=source + (len - T::one()), destination);
+        }
+    }
+
+    pub fn apply(&self, x: T) -> T {
+        shift(x, self.offsets.get(x).copied().unwrap_or(0))
+    }
+
+    /// `range` cut into pieces that each move by one offset, identity gaps included.
+    fn pieces(&self, range: RangeInclusive<T>) -> Vec<(T, T, i128)> {
+        let (start, end) = range.into_inner();
+        let mut pieces = Vec::new();
+        let mut next = Some(start);
+
+        for (r, &offset) in self.offsets.overlapping(start..=end) {
+            let (s, e) = r.into_inner();
+            if let Some(n) = next.filter(|&n| n < s) {
+                pieces.push((n, predecessor(s).unwrap(), 0));
+            }
+            pieces.push((s, e, offset));
+            next = successor(e);
+        }
+
+        if let Some(n) = next.filter(|&n| n <= end) {
+            pieces.push((n, end, 0));
+        }
+        pieces
+    }
+
+    /// The image of every value in `set`.
+    pub fn apply_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        set.intervals()
+            .flat_map(|r| self.pieces(r))
+            .map(|(s, e, offset)| shift(s, offset)..=shift(e, offset))
+            .collect()
+    }
+
+    /// The map that applies `self` and then `then`, so a whole pipeline of stages can be
+    /// folded into one.
+    pub fn compose(&self, then: &Self) -> Self {
+        let mut composed = Self::new();
+        for (s, e, first) in self.pieces(T::min_value()..=T::max_value()) {
+            for (a, b, second) in then.pieces(shift(s, first)..=shift(e, first)) {
+                let total = first.checked_add(second).expect("offset doesn't fit in an i128");
+                if total != 0 {
+                    composed.offsets.insert(shift(a, -first)..=shift(b, -first), total);
+                }
+            }
+        }
+        composed
+    }
+
+    /// The map undoing `self`, if `self` is one-to-one.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut images: Vec<(T, T, i128)> = self
+            .pieces(T::min_value()..=T::max_value())
+            .into_iter()
+            .map(|(s, e, offset)| (shift(s, offset), shift(e, offset), -offset))
+            .collect();
+        images.sort_unstable();
+
+        if images.windows(2).any(|w| w[1].0 <= w[0].1) {
+            return None;
+        }
+
+        let mut inverse = Self::new();
+        for (s, e, offset) in images.into_iter().filter(|&(_, _, offset)| offset != 0) {
+            inverse.offsets.insert(s..=e, offset);
+        }
+        Some(inverse)
+    }
+
+    /// The shifted source intervals and their offsets.
+    pub fn shifts(&self) -> impl Iterator<Item = (RangeInclusive<T>, i128)> + '_ {
+        self.offsets.iter().map(|(r, &offset)| (r, offset))
+    }
+}
+
+impl<T: PrimInt> FromIterator<(T, T, T)> for PiecewiseShiftMap<T> {
+    /// Builds a stage from `(destination, source, len)` almanac lines.
+    fn from_iter<I: IntoIterator<Item = (T, T, T)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (destination, source, len) in iter {
+            map.insert_len(destination, source, len);
+        }
+        map
+    }
+}
+
+#[test]
+fn check_intervals() {
+    // 2025 day5: fresh ingredient ranges, some overlapping.
+    let fresh: IntervalSet<u64> = IntervalSet::parse_lines("3-5\n10-14\n16-20\n12-18\n").unwrap();
+    assert_eq!(fresh.intervals().collect::<Vec<_>>(), [3..=5, 10..=20]);
+    assert_eq!([1, 5, 8, 11, 17, 32].iter().filter(|&&id| fresh.contains(id)).count(), 3);
+    assert_eq!(fresh.len(), 14);
+
+    assert_eq!(fresh.complement(0..=25).intervals().collect::<Vec<_>>(), [0..=2, 6..=9, 21..=25]);
+    assert_eq!(fresh.union(&fresh.complement(0..=25)), IntervalSet::from_iter([0..=25]));
+    assert!(fresh.intersection(&fresh.complement(0..=25)).is_empty());
+    assert!(fresh.contains_range(&(11..=19)) && !fresh.contains_range(&(4..=10)));
+    assert!(fresh.overlaps(&(6..=10)) && !fresh.overlaps(&(6..=9)));
+
+    let mut cut = fresh.clone();
+    cut.remove(12..=13);
+    cut.insert(6..=8);
+    assert_eq!(cut.intervals().collect::<Vec<_>>(), [3..=8, 10..=11, 14..=20]);
+    assert_eq!(cut.shifted(-3).intervals().next(), Some(0..=5));
+
+    // Signed ends and the full range of the type.
+    let signed: IntervalSet<i8> = ["-5--3", "-2-4", "100-127"].iter().map(|s| parse_inclusive(s).unwrap()).collect();
+    assert_eq!(signed.intervals().collect::<Vec<_>>(), [-5..=4, 100..=127]);
+    assert_eq!(signed.complement(i8::MIN..=i8::MAX).len(), 256 - 10 - 28);
+    assert_eq!(IntervalSet::from_iter([i128::MIN..=i128::MAX - 1]).len(), u128::MAX);
+    assert_eq!(parse_inclusive::<u32>("5-3"), Err(IntervalParseError::Reversed("5-3".to_string())));
+
+    // Values past i128::MAX shift like any other.
+    let top = u128::MAX - 9;
+    let high: IntervalSet<u128> = [top..=u128::MAX, 1 << 127..=(1 << 127) + 5].into_iter().collect();
+    assert_eq!(high.shifted(-10).intervals().collect::<Vec<_>>(), [(1 << 127) - 10..=(1 << 127) - 5, top - 10..=u128::MAX - 10]);
+    let low = i128::MAX as u128;
+    assert_eq!(IntervalSet::from_iter([0..=9]).shifted(i128::MAX).intervals().next(), Some(low..=low + 9));
+
+    let mut soil: IntervalMap<u32, i64> = IntervalMap::new();
+    soil.insert(50..=97, 2);
+    soil.insert(98..=99, -48);
+    soil.insert(60..=69, 7);
+    assert_eq!(soil.get(79), Some(&2));
+    assert_eq!(soil.get_interval(65), Some((60..=69, &7)));
+    assert_eq!(soil.get(10), None);
+    assert_eq!(soil.overlapping(55..=98).map(|(r, _)| r).collect::<Vec<_>>(), [55..=59, 60..=69, 70..=97, 98..=98]);
+
+    soil.insert(60..=69, 2);
+    assert_eq!(soil.len(), 2);
+    soil.remove(0..=60);
+    assert_eq!(soil.keys().intervals().collect::<Vec<_>>(), [61..=99]);
+}
+
+#[test]
+fn check_almanac() {
+    let stages: [&[(u64, u64, u64)]; 7] = [
+        &[(50, 98, 2), (52, 50, 48)],
+        &[(0, 15, 37), (37, 52, 2), (39, 0, 15)],
+        &[(49, 53, 8), (0, 11, 42), (42, 0, 7), (57, 7, 4)],
+        &[(88, 18, 7), (18, 25, 70)],
+        &[(45, 77, 23), (81, 45, 19), (68, 64, 13)],
+        &[(0, 69, 1), (1, 0, 69)],
+        &[(60, 56, 37), (56, 93, 4)],
+    ];
+    let stages: Vec<PiecewiseShiftMap<u64>> = stages.iter().map(|s| s.iter().copied().collect()).collect();
+    let almanac = stages.iter().skip(1).fold(stages[0].clone(), |acc, s| acc.compose(s));
+
+    let seeds = [79, 14, 55, 13];
+    for seed in seeds {
+        assert_eq!(almanac.apply(seed), stages.iter().fold(seed, |x, s| s.apply(x)));
+    }
+    assert_eq!(seeds.map(|s| almanac.apply(s)), [82, 43, 86, 35]);
+
+    let seed_ranges: IntervalSet<u64> = [79..=92, 55..=67].into_iter().collect();
+    let locations = almanac.apply_set(&seed_ranges);
+    assert_eq!(locations.intervals().next().map(|r| *r.start()), Some(46));
+    assert_eq!(locations.len(), seed_ranges.len());
+
+    let unmap = almanac.inverse().unwrap();
+    assert_eq!(unmap.apply(35), 13);
+    assert_eq!(unmap.compose(&almanac).shifts().count(), 0);
+
+    // The identity gaps run to the top of u128, and shifts can start past i128::MAX.
+    let top = u128::MAX - 1;
+    let swaps: PiecewiseShiftMap<u128> = [(2, 0, 2), (0, 2, 2), (top, top - 2, 2), (top - 2, top, 2)].into_iter().collect();
+    assert_eq!([0, 3, top - 2, u128::MAX].map(|x| swaps.apply(x)), [2, 1, top, u128::MAX - 2]);
+    assert_eq!(swaps.inverse(), Some(swaps.clone()));
+    assert_eq!(swaps.compose(&swaps).shifts().count(), 0);
+
+    // Two sources landing on the same place have no inverse.
+    let squash: PiecewiseShiftMap<u64> = [(0, 10, 5)].into_iter().collect();
+    assert_eq!(squash.inverse(), None);
+}
@@ -0,0 +1,170 @@
+use crate::{opposite_dir_cardinal, CardinalDirectionName, Grid, Location};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jump {
+    /// Stopped at `stop`, right in front of `blocker`.
+    Blocked { stop: Location, blocker: Location },
+    /// Walked off the grid after `last`.
+    Exits { last: Location },
+}
+
+/// Sorted blocker positions per row and column, for skipping straight to the next blocker
+/// in any cardinal direction.
+#[derive(Debug, Clone)]
+pub struct JumpIndex {
+    grid: Grid,
+    rows: Vec<Vec<usize>>,
+    cols: Vec<Vec<usize>>,
+    temporary: Option<Location>,
+}
+
+impl JumpIndex {
+    pub fn new<S, T>(map: &[S], is_blocker: impl Fn(&T) -> bool) -> Self
+    where
+        S: AsRef<[T]>,
+    {
+        let grid = Grid::from_map(map);
+        let mut rows = vec![Vec::new(); grid.rows()];
+        let mut cols = vec![Vec::new(); grid.cols()];
+
+        for [r, c] in grid.cell_range() {
+            if is_blocker(&map[r].as_ref()[c]) {
+                rows[r].push(c);
+                cols[c].push(r);
+            }
+        }
+
+        Self { grid, rows, cols, temporary: None }
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Adds one extra blocker on top of the permanent ones, replacing any previous one.
+    pub fn set_temporary(&mut self, loc: Option<Location>) {
+        self.temporary = loc;
+    }
+
+    pub fn temporary(&self) -> Option<Location> {
+        self.temporary
+    }
+
+    pub fn is_blocked(&self, loc: Location) -> bool {
+        self.temporary == Some(loc) || self.rows[loc[0]].binary_search(&loc[1]).is_ok()
+    }
+
+    /// The first blocker strictly past `loc` in `direction`.
+    pub fn first_blocker(&self, loc: Location, direction: CardinalDirectionName) -> Option<Location> {
+        use CardinalDirectionName::*;
+
+        let (dim, increasing) = match direction {
+            N => (0, false),
+            S => (0, true),
+            W => (1, false),
+            E => (1, true),
+        };
+
+        let line = if dim == 0 { &self.cols[loc[1]] } else { &self.rows[loc[0]] };
+        let pos = loc[dim];
+
+        let found = if increasing {
+            line.get(line.partition_point(|&v| v <= pos)).copied()
+        } else {
+            line[..line.partition_point(|&v| v < pos)].last().copied()
+        };
+
+        let temporary = self.temporary.and_then(|t| {
+            let ahead = if increasing { t[dim] > pos } else { t[dim] < pos };
+            (t[1 - dim] == loc[1 - dim] && ahead).then_some(t[dim])
+        });
+
+        let nearest = match (found, temporary) {
+            (Some(f), Some(t)) => Some(if increasing { f.min(t) } else { f.max(t) }),
+            (f, t) => f.or(t),
+        };
+
+        nearest.map(|v| {
+            let mut blocker = loc;
+            blocker[dim] = v;
+            blocker
+        })
+    }
+
+    /// Slides from `loc` in `direction` until the next blocker or the edge of the grid.
+    pub fn jump(&self, loc: Location, direction: CardinalDirectionName) -> Jump {
+        use CardinalDirectionName::*;
+
+        match self.first_blocker(loc, direction) {
+            Some(blocker) => Jump::Blocked {
+                stop: self.grid.add_cardinal(blocker, opposite_dir_cardinal(direction)).unwrap(),
+                blocker,
+            },
+            None => {
+                let mut last = loc;
+                match direction {
+                    N => last[0] = 0,
+                    S => last[0] = self.grid.rows() - 1,
+                    W => last[1] = 0,
+                    E => last[1] = self.grid.cols() - 1,
+                }
+                Jump::Exits { last }
+            }
+        }
+    }
+}
+
+#[test]
+fn check_guard_loops() {
+    use std::collections::HashSet;
+    use CardinalDirectionName::*;
+
+    let lab = [
+        "....#.....",
+        ".........#",
+        "..........",
+        "..#.......",
+        ".......#..",
+        "..........",
+        ".#..^.....",
+        "........#.",
+        "#.........",
+        "......#...",
+    ].map(|l| l.as_bytes());
+
+    let mut index = JumpIndex::new(&lab, |&t| t == b'#');
+    let start = [6, 4];
+
+    let loops = |index: &JumpIndex| {
+        let mut seen = HashSet::new();
+        let mut state = (start, N);
+        loop {
+            let Jump::Blocked { stop, .. } = index.jump(state.0, state.1) else {
+                return false;
+            };
+
+            let turn = match state.1 { N => E, E => S, S => W, W => N };
+            state = (stop, turn);
+            if !seen.insert(state) {
+                return true;
+            }
+        }
+    };
+
+    assert!(!loops(&index));
+
+    let mut loop_count = 0;
+    for loc in index.grid().cell_range() {
+        if loc == start || index.is_blocked(loc) {
+            continue;
+        }
+
+        index.set_temporary(Some(loc));
+        loop_count += loops(&index) as usize;
+    }
+
+    index.set_temporary(None);
+    assert_eq!(loop_count, 6);
+    assert_eq!(index.jump([9, 9], N), Jump::Blocked { stop: [2, 9], blocker: [1, 9] });
+    assert_eq!(index.jump([8, 1], E), Jump::Exits { last: [8, 9] });
+}
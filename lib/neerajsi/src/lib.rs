@@ -5,6 +5,22 @@ use std::time::Instant;
 use std::iter::IntoIterator;
 use itertools::Itertools;
 
+pub mod beam;
+pub mod connectivity;
+pub mod cycle;
+pub mod digits;
+pub mod graph;
+pub mod ilp;
+pub mod image;
+pub mod interval;
+pub mod jump;
+pub mod linalg;
+pub mod numtheory;
+pub mod poly;
+pub mod push;
+pub mod search;
+pub mod spatial;
+
 pub fn read_stdin_input() -> Vec<u8>
 {
     let mut buf = Vec::new();
@@ -42,6 +58,12 @@ impl TimingBuffer {
     }
 }
 
+impl Default for TimingBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Drop for TimingBuffer {
     fn drop(&mut self) {
         self.dump();
@@ -126,7 +148,7 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             if let Some(ref mut iter) = self.current_iter {
-                while let Some(item) = iter.next() {
+                for item in iter.by_ref() {
                     self.col += 1;
                     if (self.predicate)(item) {
                         return Some((self.row, self.col - 1));
@@ -245,6 +267,12 @@ use DirectionName::*;
 
 pub const DIRECTIONS4: [DirectionName; 4] = [W, E, N, S];
 pub const DIRECTIONS8: [DirectionName; 8] = [W, E, N, S, NW, NE, SE, SW];
+pub const CARDINAL_DIRECTIONS: [CardinalDirectionName; 4] = [
+    CardinalDirectionName::W,
+    CardinalDirectionName::E,
+    CardinalDirectionName::N,
+    CardinalDirectionName::S,
+];
 
 #[test]
 fn check_directions() {
@@ -302,6 +330,8 @@ pub fn opposite_dir(direction: DirectionName) -> DirectionName {
 
 pub type Location = [usize;2];
 
+pub type Map<T> = Vec<Vec<T>>;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Grid {
     rows: usize,
@@ -363,7 +393,7 @@ impl Grid {
         })
     }
 
-    pub fn new_map<T>(&self, val: T) -> Vec<Vec<T>> 
+    pub fn new_map<T>(&self, val: T) -> Map<T> 
     where T: Clone {
 
         vec![vec![val; self.cols()]; self.rows()]
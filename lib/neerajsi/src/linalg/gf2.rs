@@ -0,0 +1,301 @@
+/// A matrix over GF(2) with each row packed into `u64` words. Bit vectors elsewhere in this
+/// module use the same packing: bit `i` is `words[i / 64] >> (i % 64) & 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitMatrix {
+    rows: usize,
+    cols: usize,
+    words: usize,
+    data: Vec<u64>,
+}
+
+pub fn get_bit(bits: &[u64], i: usize) -> bool {
+    bits[i / 64] >> (i % 64) & 1 == 1
+}
+
+pub fn set_bit(bits: &mut [u64], i: usize, value: bool) {
+    if value {
+        bits[i / 64] |= 1 << (i % 64);
+    } else {
+        bits[i / 64] &= !(1 << (i % 64));
+    }
+}
+
+pub fn weight(bits: &[u64]) -> usize {
+    bits.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+fn xor_into(dst: &mut [u64], src: &[u64]) {
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d ^= s;
+    }
+}
+
+impl BitMatrix {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words = cols.div_ceil(64);
+        Self { rows, cols, words, data: vec![0; rows * words] }
+    }
+
+    /// Builds the matrix whose column `j` has the bits of `columns[j]`, e.g. one column per
+    /// button listing the lights it toggles.
+    pub fn from_columns<S: AsRef<[usize]>>(rows: usize, columns: &[S]) -> Self {
+        let mut m = Self::new(rows, columns.len());
+        for (c, column) in columns.iter().enumerate() {
+            for &r in column.as_ref() {
+                m.set(r, c, true);
+            }
+        }
+        m
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, r: usize) -> &[u64] {
+        &self.data[r * self.words..(r + 1) * self.words]
+    }
+
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        get_bit(self.row(r), c)
+    }
+
+    pub fn set(&mut self, r: usize, c: usize, value: bool) {
+        let words = self.words;
+        set_bit(&mut self.data[r * words..(r + 1) * words], c, value);
+    }
+
+    /// `self · x` as a bit vector over the rows.
+    pub fn apply(&self, x: &[u64]) -> Vec<u64> {
+        let mut out = vec![0; self.rows.div_ceil(64)];
+        for r in 0..self.rows {
+            let parity = self.row(r).iter().zip(x).map(|(a, b)| (a & b).count_ones()).sum::<u32>() & 1;
+            set_bit(&mut out, r, parity == 1);
+        }
+        out
+    }
+
+    pub fn rank(&self) -> usize {
+        self.solve(&vec![0; self.rows.div_ceil(64)]).rank
+    }
+
+    /// Eliminates `[self | b]`, where `b` is a bit vector over the rows.
+    pub fn solve(&self, b: &[u64]) -> Gf2System {
+        // One extra bit per row holds the right-hand side.
+        let width = (self.cols + 1).div_ceil(64);
+        let mut rows: Vec<Vec<u64>> = (0..self.rows)
+            .map(|r| {
+                let mut row = vec![0; width];
+                row[..self.words].copy_from_slice(self.row(r));
+                set_bit(&mut row, self.cols, get_bit(b, r));
+                row
+            })
+            .collect();
+
+        let mut pivots = Vec::new();
+        for c in 0..self.cols {
+            let r = pivots.len();
+            let Some(p) = (r..rows.len()).find(|&p| get_bit(&rows[p], c)) else {
+                continue;
+            };
+            rows.swap(r, p);
+
+            let pivot_row = rows[r].clone();
+            for (i, row) in rows.iter_mut().enumerate() {
+                if i != r && get_bit(row, c) {
+                    xor_into(row, &pivot_row);
+                }
+            }
+
+            pivots.push(c);
+            if pivots.len() == rows.len() {
+                break;
+            }
+        }
+
+        let rank = pivots.len();
+        let inconsistent = rows[rank..].iter().any(|row| get_bit(row, self.cols));
+        let particular = (!inconsistent).then(|| {
+            let mut x = vec![0; self.words];
+            for (row, &p) in rows.iter().zip(&pivots) {
+                set_bit(&mut x, p, get_bit(row, self.cols));
+            }
+            x
+        });
+
+        let null_space = (0..self.cols)
+            .filter(|c| !pivots.contains(c))
+            .map(|f| {
+                let mut v = vec![0; self.words];
+                set_bit(&mut v, f, true);
+                for (row, &p) in rows.iter().zip(&pivots) {
+                    set_bit(&mut v, p, get_bit(row, f));
+                }
+                v
+            })
+            .collect();
+
+        Gf2System { rank, pivots, particular, null_space }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gf2System {
+    pub rank: usize,
+    pub pivots: Vec<usize>,
+    /// A solution with every free variable cleared, or `None` if there is none at all.
+    pub particular: Option<Vec<u64>>,
+    pub null_space: Vec<Vec<u64>>,
+}
+
+/// Up to this many free variables, trying every solution is cheap enough.
+const GRAY_CODE_LIMIT: usize = 20;
+
+impl Gf2System {
+    /// Every solution, each differing from the previous one by a single null-space vector
+    /// (Gray-code order). There are `2^null_space.len()` of them, so this is `None` from 64
+    /// free variables up.
+    pub fn solutions(&self) -> Option<impl Iterator<Item = Vec<u64>> + '_> {
+        if self.null_space.len() >= 64 {
+            return None;
+        }
+
+        let count = self.particular.as_ref().map_or(0, |_| 1u64 << self.null_space.len());
+        let mut current = self.particular.clone().unwrap_or_default();
+        Some((0..count).map(move |i| {
+            if i > 0 {
+                xor_into(&mut current, &self.null_space[i.trailing_zeros() as usize]);
+            }
+            current.clone()
+        }))
+    }
+
+    /// The solution with the fewest set bits, e.g. the fewest buttons to press. With few free
+    /// variables every solution is tried; otherwise solutions are searched by how many free
+    /// variables they set, which stops once that count reaches the best weight found.
+    pub fn min_weight_solution(&self) -> Option<Vec<u64>> {
+        let particular = self.particular.as_ref()?;
+        if self.null_space.len() <= GRAY_CODE_LIMIT {
+            Some(self.min_weight_gray(particular))
+        } else {
+            Some(self.min_weight_by_free_count(particular))
+        }
+    }
+
+    fn min_weight_gray(&self, particular: &[u64]) -> Vec<u64> {
+        let mut current = particular.to_vec();
+        let mut current_weight = weight(&current);
+        let mut best = (current_weight, current.clone());
+
+        for i in 1..1u64 << self.null_space.len() {
+            // Only the words the flipped vector touches change weight.
+            for (w, &v) in current.iter_mut().zip(&self.null_space[i.trailing_zeros() as usize]) {
+                if v != 0 {
+                    current_weight = current_weight + (*w ^ v).count_ones() as usize - w.count_ones() as usize;
+                    *w ^= v;
+                }
+            }
+
+            if current_weight < best.0 {
+                best = (current_weight, current.clone());
+            }
+        }
+
+        best.1
+    }
+
+    fn min_weight_by_free_count(&self, particular: &[u64]) -> Vec<u64> {
+        // Each null-space vector sets exactly one free variable, so a solution built from `n`
+        // of them weighs at least `n`.
+        fn descend(null_space: &[Vec<u64>], from: usize, chosen: usize, current: &mut [u64], best: &mut (usize, Vec<u64>)) {
+            let w = weight(current);
+            if w < best.0 {
+                *best = (w, current.to_vec());
+            }
+            if chosen + 1 >= best.0 {
+                return;
+            }
+
+            for i in from..null_space.len() {
+                xor_into(current, &null_space[i]);
+                descend(null_space, i + 1, chosen + 1, current, best);
+                xor_into(current, &null_space[i]);
+            }
+        }
+
+        let mut current = particular.to_vec();
+        let mut best = (usize::MAX, Vec::new());
+        descend(&self.null_space, 0, 0, &mut current, &mut best);
+        best.1
+    }
+}
+
+#[test]
+fn check_light_toggles() {
+    // 2025 day10 part 1.
+    let machines: [(&[usize], &[&[usize]]); 3] = [
+        (&[1, 2], &[&[3], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]]),
+        (&[3], &[&[0, 2, 3, 4], &[2, 3], &[0, 4], &[0, 1, 2], &[1, 2, 3, 4]]),
+        (&[1, 2, 3, 5], &[&[0, 1, 2, 3, 4], &[0, 3, 4], &[0, 1, 2, 4, 5], &[1, 2]]),
+    ];
+
+    let presses: Vec<usize> = machines
+        .iter()
+        .map(|&(lights, buttons)| {
+            let rows = buttons.iter().flat_map(|b| b.iter()).max().unwrap() + 1;
+            let m = BitMatrix::from_columns(rows, buttons);
+            let mut target = vec![0; rows.div_ceil(64)];
+            for &l in lights {
+                set_bit(&mut target, l, true);
+            }
+
+            let system = m.solve(&target);
+            assert!(system.solutions().unwrap().all(|x| m.apply(&x) == target));
+            weight(&system.min_weight_solution().unwrap())
+        })
+        .collect();
+    assert_eq!(presses, [2, 3, 2]);
+
+    // 100 lights, where button i toggles lights i and i + 1, plus one that toggles both ends.
+    let mut buttons: Vec<Vec<usize>> = (0..100).map(|i| if i < 99 { vec![i, i + 1] } else { vec![i] }).collect();
+    buttons.push(vec![0, 99]);
+    let m = BitMatrix::from_columns(100, &buttons);
+    assert_eq!(m.rank(), 100);
+
+    let mut target = vec![0; 2];
+    set_bit(&mut target, 0, true);
+    set_bit(&mut target, 99, true);
+    let system = m.solve(&target);
+    assert_eq!(system.null_space.len(), 1);
+    let best = system.min_weight_solution().unwrap();
+    assert_eq!((weight(&best), get_bit(&best, 100)), (1, true));
+
+    let mut odd = BitMatrix::new(2, 1);
+    odd.set(0, 0, true);
+    odd.set(1, 0, true);
+    assert_eq!(odd.solve(&[0b01]).min_weight_solution(), None);
+
+    // Too many free variables to enumerate, but the search stops at once.
+    let blank = BitMatrix::new(1, 70).solve(&[0]);
+    assert!(blank.solutions().is_none());
+    assert_eq!(blank.min_weight_solution().map(|x| weight(&x)), Some(0));
+
+    // Both strategies agree just past the enumeration limit.
+    let mut seed = 12345u64;
+    let mut wide = BitMatrix::new(4, GRAY_CODE_LIMIT + 5);
+    for r in 0..4 {
+        for c in 0..GRAY_CODE_LIMIT + 5 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            wide.set(r, c, seed >> 63 == 1);
+        }
+    }
+    let system = wide.solve(&[0b1011]);
+    let particular = system.particular.clone().unwrap();
+    let by_count = system.min_weight_by_free_count(&particular);
+    assert_eq!(wide.apply(&by_count), [0b1011]);
+    assert_eq!(weight(&by_count), weight(&system.min_weight_gray(&particular)));
+}
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use num_traits::{One, Zero};
+
+pub mod gf2;
+
+/// Anything exact that supports the four arithmetic operations: `Ratio<i64>`,
+/// `Ratio<BigInt>`, [`Gf`], ...
+pub trait Field: Clone + PartialEq + Zero + One + Sub<Output = Self> + Div<Output = Self> {}
+
+impl<T> Field for T where T: Clone + PartialEq + Zero + One + Sub<Output = T> + Div<Output = T> {}
+
+/// Integers modulo the prime `P`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Gf<const P: u64>(u64);
+
+impl<const P: u64> Gf<P> {
+    pub fn new(value: i64) -> Self {
+        Self(value.rem_euclid(P as i64) as u64)
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    pub fn pow(self, mut exp: u64) -> Self {
+        let (mut base, mut result) = (self, Self(1 % P));
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    pub fn inverse(self) -> Self {
+        assert!(self.0 != 0, "zero has no inverse");
+        self.pow(P - 2)
+    }
+}
+
+impl<const P: u64> fmt::Display for Gf<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const P: u64> Add for Gf<P> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(((self.0 as u128 + rhs.0 as u128) % P as u128) as u64)
+    }
+}
+
+impl<const P: u64> Sub for Gf<P> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl<const P: u64> Neg for Gf<P> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self((P - self.0) % P)
+    }
+}
+
+impl<const P: u64> Mul for Gf<P> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self((self.0 as u128 * rhs.0 as u128 % P as u128) as u64)
+    }
+}
+
+impl<const P: u64> Div for Gf<P> {
+    type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inverse()
+    }
+}
+
+impl<const P: u64> Zero for Gf<P> {
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl<const P: u64> One for Gf<P> {
+    fn one() -> Self {
+        Self(1 % P)
+    }
+}
+
+/// Brings the first `cols` columns of `rows` into reduced row echelon form in place, applying
+/// the same row operations to any columns past `cols`. Returns the pivot column of each of the
+/// first `rank` rows.
+pub fn reduce<T: Field>(rows: &mut [Vec<T>], cols: usize) -> Vec<usize> {
+    let mut pivots = Vec::new();
+
+    for c in 0..cols {
+        let r = pivots.len();
+        let Some(p) = (r..rows.len()).find(|&p| !rows[p][c].is_zero()) else {
+            continue;
+        };
+        rows.swap(r, p);
+
+        let scale = rows[r][c].clone();
+        for v in rows[r].iter_mut() {
+            *v = v.clone() / scale.clone();
+        }
+
+        let pivot_row = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != r && !row[c].is_zero() {
+                let factor = row[c].clone();
+                for (v, p) in row.iter_mut().zip(&pivot_row) {
+                    *v = v.clone() - factor.clone() * p.clone();
+                }
+            }
+        }
+
+        pivots.push(c);
+        if pivots.len() == rows.len() {
+            break;
+        }
+    }
+
+    pivots
+}
+
+/// Everything RREF tells us about `A x = b`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearSystem<T> {
+    /// `[A | b]` in reduced row echelon form.
+    pub reduced: Vec<Vec<T>>,
+    pub rank: usize,
+    pub pivots: Vec<usize>,
+    /// Some row reduced to `0 = nonzero`.
+    pub inconsistent: bool,
+    /// A solution with every free variable set to zero, unless inconsistent.
+    pub particular: Option<Vec<T>>,
+    /// One basis vector per free variable.
+    pub null_space: Vec<Vec<T>>,
+}
+
+impl<T: Field> LinearSystem<T> {
+    pub fn solve<S: AsRef<[T]>>(a: &[S], b: &[T]) -> Self {
+        assert_eq!(a.len(), b.len());
+        let cols = a.first().map_or(0, |r| r.as_ref().len());
+
+        let mut reduced: Vec<Vec<T>> = a.iter().zip(b).map(|(row, v)| row.as_ref().iter().cloned().chain([v.clone()]).collect()).collect();
+        let pivots = reduce(&mut reduced, cols);
+        let rank = pivots.len();
+
+        let inconsistent = reduced[rank..].iter().any(|row| !row[cols].is_zero());
+        let particular = (!inconsistent).then(|| {
+            let mut x = vec![T::zero(); cols];
+            for (row, &p) in reduced.iter().zip(&pivots) {
+                x[p] = row[cols].clone();
+            }
+            x
+        });
+
+        let null_space = free_columns(&pivots, cols)
+            .map(|f| {
+                let mut v = vec![T::zero(); cols];
+                v[f] = T::one();
+                for (row, &p) in reduced.iter().zip(&pivots) {
+                    v[p] = T::zero() - row[f].clone();
+                }
+                v
+            })
+            .collect();
+
+        Self { reduced, rank, pivots, inconsistent, particular, null_space }
+    }
+
+    pub fn free_variables(&self) -> Vec<usize> {
+        free_columns(&self.pivots, self.reduced.first().map_or(0, |r| r.len() - 1)).collect()
+    }
+}
+
+fn free_columns(pivots: &[usize], cols: usize) -> impl Iterator<Item = usize> + '_ {
+    (0..cols).filter(move |c| !pivots.contains(c))
+}
+
+#[test]
+fn check_linear_systems() {
+    use num_rational::Rational64;
+
+    let r = |v: i64| Rational64::from_integer(v);
+
+    // 2024 day13: button A moves (94, 34), B moves (22, 67), prize at (8400, 5400).
+    let claw = LinearSystem::solve(&[[r(94), r(22)], [r(34), r(67)]], &[r(8400), r(5400)]);
+    assert_eq!((claw.rank, claw.particular), (2, Some(vec![r(80), r(40)])));
+    assert!(claw.null_space.is_empty());
+
+    // 2025 day10: buttons (3) (1,3) (2) (2,3) (0,2) (0,1) against joltages {3,5,4,7}.
+    let buttons: [&[usize]; 6] = [&[3], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]];
+    let a: Vec<Vec<Rational64>> = (0..4).map(|row| buttons.iter().map(|b| r(b.contains(&row) as i64)).collect()).collect();
+    let b = [r(3), r(5), r(4), r(7)];
+    let machine = LinearSystem::solve(&a, &b);
+    assert_eq!((machine.rank, machine.free_variables()), (4, vec![3, 5]));
+    assert!(!machine.inconsistent);
+
+    let apply = |x: &[Rational64]| -> Vec<Rational64> { a.iter().map(|row| row.iter().zip(x).map(|(p, q)| p * q).sum()).collect() };
+    let particular = machine.particular.clone().unwrap();
+    assert_eq!(apply(&particular), b);
+    for v in &machine.null_space {
+        assert!(apply(v).iter().all(|x| x.is_zero()));
+    }
+
+    let contradiction = LinearSystem::solve(&[[r(1), r(1)], [r(2), r(2)]], &[r(1), r(3)]);
+    assert!(contradiction.inconsistent && contradiction.particular.is_none());
+
+    // Over GF(7): x + 2y = 3, 3x + y = 4 gives x = 1, y = 1.
+    let g = Gf::<7>::new;
+    let gf = LinearSystem::solve(&[[g(1), g(2)], [g(3), g(1)]], &[g(3), g(4)]);
+    assert_eq!(gf.particular, Some(vec![g(1), g(1)]));
+    assert_eq!(g(3) / g(5), g(2));
+}
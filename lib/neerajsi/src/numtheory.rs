@@ -0,0 +1,124 @@
+use num_traits::AsPrimitive;
+
+/// Returns `(g, x, y)` with `a * x + b * y == g == gcd(a, b)`.
+pub fn ext_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The inverse of `a` modulo `m`, in `0..m`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    let (g, x, _) = ext_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// `a * b % m` without overflowing, for `a, b < m`.
+pub fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if let Some(p) = a.checked_mul(b) {
+        return p % m;
+    }
+
+    let (mut a, mut b, mut result) = (a % m, b, 0u128);
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+    result
+}
+
+fn add_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub fn checked_lcm(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+pub fn gcd_of<T: AsPrimitive<u128>>(values: impl IntoIterator<Item = T>) -> u128 {
+    values.into_iter().fold(0, |acc, v| gcd(acc, v.as_()))
+}
+
+/// `None` if the result doesn't fit in a `u128`.
+pub fn lcm_of<T: AsPrimitive<u128>>(values: impl IntoIterator<Item = T>) -> Option<u128> {
+    values.into_iter().try_fold(1, |acc, v| checked_lcm(acc, v.as_()))
+}
+
+/// Merges `x = a1 (mod m1)` and `x = a2 (mod m2)` into `x = a (mod lcm(m1, m2))`. The moduli
+/// needn't be coprime; `None` means the congruences contradict each other or the combined
+/// modulus overflows.
+pub fn crt_pair((a1, m1): (u128, u128), (a2, m2): (u128, u128)) -> Option<(u128, u128)> {
+    let (a1, a2) = (a1 % m1, a2 % m2);
+    let g = gcd(m1, m2);
+    let diff = if a2 >= a1 { (a2 - a1) % m2 } else { m2 - (a1 - a2) % m2 };
+    if diff % g != 0 {
+        return None;
+    }
+
+    let lcm = checked_lcm(m1, m2)?;
+    let step = m2 / g;
+    if step == 1 {
+        return Some((a1, lcm));
+    }
+
+    // Solve m1 * k = diff (mod m2) for k in 0..step.
+    let inv = mod_inverse((m1 / g % step) as i128, step as i128)? as u128;
+    let k = mul_mod(diff / g % step, inv, step);
+    Some(((a1 + mul_mod(m1, k, lcm)) % lcm, lcm))
+}
+
+/// Solves a whole system of `(residue, modulus)` congruences.
+pub fn crt(congruences: impl IntoIterator<Item = (u128, u128)>) -> Option<(u128, u128)> {
+    congruences.into_iter().try_fold((0, 1), crt_pair)
+}
+
+#[test]
+fn check_number_theory() {
+    let (g, x, y) = ext_gcd(240, 46);
+    assert_eq!((g, 240 * x + 46 * y), (2, 2));
+    assert_eq!(mod_inverse(3, 11), Some(4));
+    assert_eq!(mod_inverse(-3, 11), Some(7));
+    assert_eq!(mod_inverse(6, 9), None);
+
+    assert_eq!(gcd_of([12u32, 18, 30]), 6);
+    assert_eq!(lcm_of([2usize, 3, 4, 5]), Some(60));
+    assert_eq!(lcm_of([u128::MAX, 2]), None);
+    assert_eq!(mul_mod(u128::MAX - 1, u128::MAX - 1, u128::MAX), 1);
+
+    // 2024 day14: the robots line up horizontally every 101 steps and vertically every 103.
+    assert_eq!(crt([(12, 101), (88, 103)]), Some((6577, 101 * 103)));
+
+    assert_eq!(crt([(2, 6), (8, 10)]), Some((8, 30)));
+    assert_eq!(crt([(1, 4), (2, 6)]), None);
+    assert_eq!(crt([(0, 2), (1, 3), (2, 4), (3, 5), (4, 6)]), Some((58, 60)));
+}
@@ -0,0 +1,200 @@
+use std::ops::RangeInclusive;
+
+use num_rational::Ratio;
+use num_traits::Zero;
+
+type Q = Ratio<i128>;
+
+/// Rows of successive differences, starting with `values` itself and ending at the first
+/// row that is all zeros (or has a single entry).
+pub fn difference_table(values: &[i64]) -> Vec<Vec<i64>> {
+    let mut table = vec![values.to_vec()];
+    loop {
+        let last = table.last().unwrap();
+        if last.len() <= 1 || last.iter().all(|&v| v == 0) {
+            return table;
+        }
+        let next = last.windows(2).map(|w| w[1] - w[0]).collect();
+        table.push(next);
+    }
+}
+
+/// The lowest-degree polynomial through a set of integer samples, kept in Newton form:
+/// `c0 + c1 (x - x0) + c2 (x - x0)(x - x1) + ...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewtonPoly {
+    nodes: Vec<i64>,
+    coefficients: Vec<Q>,
+    samples: usize,
+}
+
+impl NewtonPoly {
+    /// Interpolates `(x, y)` samples with distinct `x` by divided differences.
+    pub fn fit(points: &[(i64, i64)]) -> Self {
+        let nodes: Vec<i64> = points.iter().map(|&(x, _)| x).collect();
+        let mut column: Vec<Q> = points.iter().map(|&(_, y)| Q::from(y as i128)).collect();
+        let mut coefficients = Vec::with_capacity(points.len());
+
+        for level in 0..points.len() {
+            coefficients.push(column[0]);
+            column = (0..column.len() - 1)
+                .map(|i| {
+                    let dx = (nodes[i + level + 1] - nodes[i]) as i128;
+                    assert!(dx != 0, "sample x values must be distinct");
+                    (column[i + 1] - column[i]) / Q::from(dx)
+                })
+                .collect();
+        }
+
+        while coefficients.last().is_some_and(|c| c.is_zero()) {
+            coefficients.pop();
+        }
+
+        Self { nodes, coefficients, samples: points.len() }
+    }
+
+    /// Fits `values[i]` at `x = i`, as in a puzzle's sequence of readings.
+    pub fn from_sequence(values: &[i64]) -> Self {
+        let points: Vec<(i64, i64)> = values.iter().enumerate().map(|(i, &v)| (i as i64, v)).collect();
+        Self::fit(&points)
+    }
+
+    /// `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    /// Whether there were more samples than needed to pin down the polynomial, so at least
+    /// one of them confirms the fit rather than defining it.
+    pub fn is_overdetermined(&self) -> bool {
+        self.coefficients.len() < self.samples
+    }
+
+    pub fn eval(&self, x: i64) -> Q {
+        // Horner's rule on the nested Newton form.
+        self.coefficients
+            .iter()
+            .zip(&self.nodes)
+            .rev()
+            .fold(Q::zero(), |acc, (&c, &node)| acc * Q::from((x - node) as i128) + c)
+    }
+
+    /// The value at `x` if it is an integer.
+    pub fn eval_integer(&self, x: i64) -> Option<i128> {
+        let v = self.eval(x);
+        v.is_integer().then(|| v.to_integer())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inequality {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+/// The integers `x` in `range` with `a x² + b x + c` compared to zero by `inequality`, as
+/// at most two disjoint intervals. Boundaries come from the integer square root of the
+/// discriminant and are then checked exactly, so large inputs are safe from rounding.
+/// Panics if the discriminant overflows an `i128`.
+pub fn quadratic_solutions(a: i128, b: i128, c: i128, inequality: Inequality, range: RangeInclusive<i64>) -> Vec<RangeInclusive<i64>> {
+    let value = |x: i64| (a * x as i128 + b) * x as i128 + c;
+    let holds = |x: i64| match inequality {
+        Inequality::Greater => value(x) > 0,
+        Inequality::GreaterOrEqual => value(x) >= 0,
+        Inequality::Less => value(x) < 0,
+        Inequality::LessOrEqual => value(x) <= 0,
+    };
+
+    // Integers within one of a real root; the sign can only change next to one of these.
+    let mut roots = Vec::new();
+    if a != 0 {
+        let discriminant = b
+            .checked_mul(b)
+            .zip(a.checked_mul(c).and_then(|ac| ac.checked_mul(4)))
+            .and_then(|(bb, ac4)| bb.checked_sub(ac4))
+            .expect("discriminant overflows i128");
+        if discriminant >= 0 {
+            let s = discriminant.unsigned_abs().isqrt() as i128;
+            roots.push((-b - s).div_euclid(2 * a));
+            roots.push((-b + s).div_euclid(2 * a));
+        }
+    } else if b != 0 {
+        roots.push((-c).div_euclid(b));
+    }
+
+    let (lo, hi) = (*range.start() as i128, *range.end() as i128);
+    let mut points = vec![lo, hi];
+    for r in roots {
+        points.extend((r - 1..=r + 2).filter(|p| (lo..=hi).contains(p)));
+    }
+    points.sort_unstable();
+    points.dedup();
+
+    // Between consecutive breakpoints the sign is constant, so one probe per gap suffices.
+    let mut intervals: Vec<RangeInclusive<i64>> = Vec::new();
+    let mut extend = |from: i64, to: i64| match intervals.last_mut() {
+        Some(last) if *last.end() + 1 == from => *last = *last.start()..=to,
+        _ => intervals.push(from..=to),
+    };
+
+    for (i, &p) in points.iter().enumerate() {
+        let p = p as i64;
+        if holds(p) {
+            extend(p, p);
+        }
+        if let Some(&next) = points.get(i + 1) {
+            let next = next as i64;
+            if next > p + 1 && holds(p + 1) {
+                extend(p + 1, next - 1);
+            }
+        }
+    }
+
+    intervals
+}
+
+pub fn count_quadratic_solutions(a: i128, b: i128, c: i128, inequality: Inequality, range: RangeInclusive<i64>) -> u64 {
+    quadratic_solutions(a, b, c, inequality, range).iter().map(|r| (r.end() - r.start()) as u64 + 1).sum()
+}
+
+#[test]
+fn check_sequences() {
+    let histories: [&[i64]; 3] = [&[0, 3, 6, 9, 12, 15], &[1, 3, 6, 10, 15, 21], &[10, 13, 16, 21, 30, 45]];
+    let polys: Vec<NewtonPoly> = histories.iter().map(|h| NewtonPoly::from_sequence(h)).collect();
+
+    assert_eq!(polys.iter().map(|p| p.degree().unwrap()).collect::<Vec<_>>(), [1, 2, 3]);
+    assert!(polys.iter().all(|p| p.is_overdetermined()));
+    assert_eq!(polys.iter().map(|p| p.eval_integer(6).unwrap()).sum::<i128>(), 114);
+    assert_eq!(polys.iter().map(|p| p.eval_integer(-1).unwrap()).sum::<i128>(), 2);
+    assert_eq!(difference_table(histories[2]).len(), 5);
+
+    // Three samples of a quadratic spaced one map-width apart, as in 2023 day21.
+    let f = |n: i64| 14_650 * (n as i128).pow(2) + 3_641 * n as i128 - 7;
+    let samples: Vec<(i64, i64)> = [65, 196, 327].iter().map(|&n| (n, f(n) as i64)).collect();
+    let quadratic = NewtonPoly::fit(&samples);
+    assert_eq!(quadratic.degree(), Some(2));
+    assert!(!quadratic.is_overdetermined());
+    assert_eq!(quadratic.eval_integer(26501365), Some(f(26501365)));
+
+    assert_eq!(NewtonPoly::from_sequence(&[0, 0, 0]).degree(), None);
+}
+
+#[test]
+fn check_ways_to_win() {
+    // 2023 day06: holding the button for x of t ms travels (t - x) x, which must beat d.
+    let ways = |t: i64, d: i128| count_quadratic_solutions(-1, t as i128, -d, Inequality::Greater, 0..=t);
+    assert_eq!(ways(7, 9) * ways(15, 40) * ways(30, 200), 288);
+    assert_eq!(ways(71530, 940200), 71503);
+    assert_eq!(quadratic_solutions(-1, 7, -9, Inequality::Greater, 0..=7), [2..=5]);
+
+    // Exact squares just past 2^53, where f64 can't tell k² from k² + 1.
+    let k = (1i64 << 53) + 1;
+    let square = k as i128 * k as i128;
+    assert_eq!(quadratic_solutions(1, 0, -square, Inequality::LessOrEqual, 0..=1 << 60), [0..=k]);
+    assert_eq!(quadratic_solutions(1, 0, -square, Inequality::Greater, -(1 << 60)..=1 << 60), [-(1 << 60)..=-k - 1, k + 1..=1 << 60]);
+
+    assert_eq!(count_quadratic_solutions(0, 2, -5, Inequality::GreaterOrEqual, 0..=10), 8);
+    assert_eq!(count_quadratic_solutions(1, 0, 1, Inequality::Less, -5..=5), 0);
+}
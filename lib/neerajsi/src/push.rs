@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+
+use crate::{opposite_dir_cardinal, CardinalDirectionName, Grid, Location, Map};
+
+pub type ObjectId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PushObject {
+    pub origin: Location,
+    pub size: [usize; 2],
+}
+
+impl PushObject {
+    pub fn cells(&self) -> impl Iterator<Item = Location> + use<> {
+        let [r0, c0] = self.origin;
+        let [h, w] = self.size;
+        (r0..r0 + h).flat_map(move |r| (c0..c0 + w).map(move |c| [r, c]))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PushRecord {
+    direction: CardinalDirectionName,
+    moved: Vec<ObjectId>,
+}
+
+/// Sokoban-style pushing of rectangular objects around fixed walls.
+#[derive(Debug, Clone)]
+pub struct PushWorld {
+    grid: Grid,
+    walls: Map<bool>,
+    occupancy: Map<Option<ObjectId>>,
+    objects: Vec<PushObject>,
+    history: Vec<PushRecord>,
+}
+
+impl PushWorld {
+    pub fn new<S, T>(map: &[S], is_wall: impl Fn(&T) -> bool) -> Self
+    where
+        S: AsRef<[T]>,
+    {
+        let grid = Grid::from_map(map);
+        let walls = map.iter().map(|r| r.as_ref().iter().map(&is_wall).collect()).collect();
+
+        Self {
+            grid,
+            walls,
+            occupancy: grid.new_map(None),
+            objects: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Places a new object, or returns `None` if it is empty, leaves the grid, or would
+    /// overlap a wall or another object.
+    pub fn add_object(&mut self, origin: Location, size: [usize; 2]) -> Option<ObjectId> {
+        if size.contains(&0) {
+            return None;
+        }
+
+        let far_corner = [origin[0].checked_add(size[0])? - 1, origin[1].checked_add(size[1])? - 1];
+        if !self.grid.in_bounds(&far_corner) {
+            return None;
+        }
+
+        let object = PushObject { origin, size };
+
+        if object.cells().any(|[r, c]| self.walls[r][c] || self.occupancy[r][c].is_some()) {
+            return None;
+        }
+
+        let id = self.objects.len();
+        self.objects.push(object);
+        self.stamp(id, Some(id));
+        Some(id)
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn object(&self, id: ObjectId) -> &PushObject {
+        &self.objects[id]
+    }
+
+    pub fn objects(&self) -> &[PushObject] {
+        &self.objects
+    }
+
+    pub fn object_at(&self, loc: Location) -> Option<ObjectId> {
+        self.occupancy[loc[0]][loc[1]]
+    }
+
+    pub fn is_wall(&self, loc: Location) -> bool {
+        self.walls[loc[0]][loc[1]]
+    }
+
+    /// Every object that would move if `id` were pushed, starting with `id` itself, or
+    /// `None` if any of them would run into a wall or off the grid.
+    pub fn objects_moved_by(&self, id: ObjectId, direction: CardinalDirectionName) -> Option<Vec<ObjectId>> {
+        let mut in_set = vec![false; self.objects.len()];
+        let mut moved = vec![id];
+        let mut queue = VecDeque::from([id]);
+        in_set[id] = true;
+
+        while let Some(o) = queue.pop_front() {
+            for cell in self.objects[o].cells() {
+                let next = self.grid.add_cardinal(cell, direction)?;
+                if self.is_wall(next) {
+                    return None;
+                }
+
+                if let Some(other) = self.object_at(next) {
+                    if !in_set[other] {
+                        in_set[other] = true;
+                        moved.push(other);
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+
+        Some(moved)
+    }
+
+    /// Pushes `id` one cell in `direction`, moving everything in the way along with it.
+    /// Nothing moves if the push is blocked.
+    pub fn push(&mut self, id: ObjectId, direction: CardinalDirectionName) -> Option<&[ObjectId]> {
+        let moved = self.objects_moved_by(id, direction)?;
+        self.shift(&moved, direction);
+        self.history.push(PushRecord { direction, moved });
+        Some(&self.history.last().unwrap().moved)
+    }
+
+    /// Reverts the most recent successful push, returning the objects it moved back.
+    pub fn undo(&mut self) -> Option<Vec<ObjectId>> {
+        let record = self.history.pop()?;
+        self.shift(&record.moved, opposite_dir_cardinal(record.direction));
+        Some(record.moved)
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    fn shift(&mut self, moved: &[ObjectId], direction: CardinalDirectionName) {
+        for &o in moved {
+            self.stamp(o, None);
+        }
+
+        for &o in moved {
+            let origin = self.objects[o].origin;
+            self.objects[o].origin = self.grid.add_cardinal(origin, direction).unwrap();
+            self.stamp(o, Some(o));
+        }
+    }
+
+    fn stamp(&mut self, id: ObjectId, value: Option<ObjectId>) {
+        for [r, c] in self.objects[id].cells() {
+            self.occupancy[r][c] = value;
+        }
+    }
+}
+
+#[test]
+fn check_wide_boxes() {
+    use CardinalDirectionName::*;
+
+    let warehouse = [
+        "#######",
+        "#...#.#",
+        "#.....#",
+        "#..OO@#",
+        "#..O..#",
+        "#.....#",
+        "#######",
+    ];
+
+    let wide = warehouse.map(|l| l.bytes().flat_map(|b| [b, b]).collect::<Vec<u8>>());
+    let mut world = PushWorld::new(&wide, |&t| t == b'#');
+
+    let mut robot = None;
+    for (r, row) in warehouse.iter().enumerate() {
+        for (c, t) in row.bytes().enumerate() {
+            match t {
+                b'O' => {
+                    world.add_object([r, c * 2], [1, 2]).unwrap();
+                }
+                b'@' => robot = world.add_object([r, c * 2], [1, 1]),
+                _ => {}
+            }
+        }
+    }
+
+    let robot = robot.unwrap();
+    let before = world.objects().to_vec();
+    for d in [W, S, S, S, W, W, N, N, W, W, N, N] {
+        world.push(robot, d);
+    }
+
+    let gps: usize = world
+        .objects()
+        .iter()
+        .enumerate()
+        .filter(|&(id, _)| id != robot)
+        .map(|(_, o)| o.origin[0] * 100 + o.origin[1])
+        .sum();
+    assert_eq!(gps, 618);
+
+    while world.undo().is_some() {}
+    assert_eq!(world.objects(), &before[..]);
+
+    assert_eq!(world.add_object([0, 0], [0, 1]), None);
+    assert_eq!(world.add_object([usize::MAX, 1], [1, 1]), None);
+}
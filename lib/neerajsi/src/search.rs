@@ -0,0 +1,398 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use num_traits::Zero;
+
+use crate::{DirectionIterator, Grid, Location, Map};
+
+pub trait SearchState: Clone + Eq + Hash {
+    type Cost: Copy + Ord + Zero + Add<Output = Self::Cost>;
+    type Context: ?Sized;
+
+    fn successors(&self, ctx: &Self::Context, out: &mut Vec<(Self, Self::Cost)>);
+    fn is_goal(&self, ctx: &Self::Context) -> bool;
+
+    /// Must be consistent: never more than a step's cost plus the heuristic after it, since
+    /// settled states are never reopened. The default turns A* into Dijkstra.
+    fn heuristic(&self, _ctx: &Self::Context) -> Self::Cost {
+        Self::Cost::zero()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node<S: SearchState> {
+    state: S,
+    cost: S::Cost,
+    preds: Vec<usize>,
+    start: bool,
+    settled: bool,
+}
+
+/// Every optimal way to reach a goal, kept as a DAG of equal-cost predecessors.
+#[derive(Debug, Clone)]
+pub struct SearchResult<S: SearchState> {
+    pub cost: S::Cost,
+    nodes: Vec<Node<S>>,
+    goals: Vec<usize>,
+}
+
+impl<S: SearchState> SearchResult<S> {
+    pub fn goals(&self) -> impl Iterator<Item = &S> {
+        self.goals.iter().map(|&g| &self.nodes[g].state)
+    }
+
+    /// One optimal path, from a start state to a goal.
+    pub fn path(&self) -> Vec<S> {
+        let mut path = Vec::new();
+        let mut cur = Some(self.goals[0]);
+        while let Some(n) = cur {
+            path.push(self.nodes[n].state.clone());
+            cur = self.nodes[n].preds.first().copied();
+        }
+
+        path.reverse();
+        path
+    }
+
+    fn on_optimal_mask(&self) -> Vec<bool> {
+        let mut mask = vec![false; self.nodes.len()];
+        let mut stack = self.goals.clone();
+        for &g in &self.goals {
+            mask[g] = true;
+        }
+
+        while let Some(n) = stack.pop() {
+            for &p in &self.nodes[n].preds {
+                if !mask[p] {
+                    mask[p] = true;
+                    stack.push(p);
+                }
+            }
+        }
+
+        mask
+    }
+
+    /// Every state that lies on at least one optimal path.
+    pub fn states_on_optimal_paths(&self) -> Vec<S> {
+        let mask = self.on_optimal_mask();
+        self.nodes
+            .iter()
+            .zip(mask)
+            .filter(|&(_, on)| on)
+            .map(|(n, _)| n.state.clone())
+            .collect()
+    }
+
+    /// `None` if a cycle of zero-cost steps lies on an optimal path, making the count infinite.
+    pub fn optimal_path_count(&self) -> Option<u128> {
+        let mask = self.on_optimal_mask();
+        let mut counts = vec![0u128; self.nodes.len()];
+        let mut waiting: Vec<usize> = self.nodes.iter().map(|n| n.preds.len()).collect();
+        let mut next = vec![Vec::new(); self.nodes.len()];
+        for (m, node) in self.nodes.iter().enumerate().filter(|&(m, _)| mask[m]) {
+            for &p in &node.preds {
+                next[p].push(m);
+            }
+        }
+
+        // Zero-cost steps can reach a state after it has settled, so walk the predecessor
+        // graph in topological order rather than settle order.
+        for (n, node) in self.nodes.iter().enumerate() {
+            counts[n] = node.start as u128;
+        }
+        let mut ready: Vec<usize> = (0..self.nodes.len()).filter(|&n| mask[n] && waiting[n] == 0).collect();
+
+        while let Some(n) = ready.pop() {
+            for &m in &next[n] {
+                counts[m] += counts[n];
+                waiting[m] -= 1;
+                if waiting[m] == 0 {
+                    ready.push(m);
+                }
+            }
+        }
+
+        if self.goals.iter().any(|&g| waiting[g] != 0) {
+            return None;
+        }
+        Some(self.goals.iter().map(|&g| counts[g]).sum())
+    }
+}
+
+/// Dijkstra (or A* with a heuristic) from all `starts` at once. Returns `None` when no
+/// goal is reachable.
+pub fn search<S: SearchState>(ctx: &S::Context, starts: impl IntoIterator<Item = S>) -> Option<SearchResult<S>> {
+    let mut index: HashMap<S, usize> = HashMap::new();
+    let mut nodes: Vec<Node<S>> = Vec::new();
+    let mut heap = BinaryHeap::new();
+
+    for s in starts {
+        if let Entry::Vacant(e) = index.entry(s.clone()) {
+            e.insert(nodes.len());
+            heap.push(Reverse((s.heuristic(ctx), S::Cost::zero(), nodes.len())));
+            nodes.push(Node { state: s, cost: S::Cost::zero(), preds: Vec::new(), start: true, settled: false });
+        }
+    }
+
+    let mut best = None;
+    let mut goals = Vec::new();
+    let mut successors = Vec::new();
+
+    while let Some(Reverse((f, cost, n))) = heap.pop() {
+        if best.is_some_and(|b| f > b) {
+            break;
+        }
+
+        if nodes[n].settled || cost > nodes[n].cost {
+            continue;
+        }
+
+        nodes[n].settled = true;
+
+        if nodes[n].state.is_goal(ctx) {
+            best = Some(cost);
+            goals.push(n);
+            continue;
+        }
+
+        successors.clear();
+        nodes[n].state.successors(ctx, &mut successors);
+        for (next, step) in successors.drain(..) {
+            let next_cost = cost + step;
+            match index.entry(next) {
+                Entry::Vacant(e) => {
+                    let h = e.key().heuristic(ctx);
+                    let m = nodes.len();
+                    nodes.push(Node { state: e.key().clone(), cost: next_cost, preds: vec![n], start: false, settled: false });
+                    e.insert(m);
+                    heap.push(Reverse((next_cost + h, next_cost, m)));
+                }
+                Entry::Occupied(e) => {
+                    let m = *e.get();
+                    let node = &mut nodes[m];
+                    if next_cost > node.cost {
+                        continue;
+                    }
+
+                    // A zero-cost step can tie with a state that has already settled.
+                    if node.settled {
+                        node.preds.push(n);
+                        continue;
+                    }
+
+                    if next_cost < node.cost {
+                        node.cost = next_cost;
+                        node.preds.clear();
+                        heap.push(Reverse((next_cost + e.key().heuristic(ctx), next_cost, m)));
+                    }
+
+                    node.preds.push(n);
+                }
+            }
+        }
+    }
+
+    Some(SearchResult { cost: best?, nodes, goals })
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FieldOptions {
+    /// Also step diagonally.
+    pub diagonal: bool,
+    /// Stop as soon as this cell has a distance.
+    pub target: Option<Location>,
+}
+
+fn field_neighbors(grid: &Grid, loc: Location, diagonal: bool) -> DirectionIterator {
+    if diagonal {
+        grid.neighbors(loc)
+    } else {
+        grid.neighbors_cardinal(loc)
+    }
+}
+
+/// Shortest step counts from the nearest of `sources` to every reachable passable cell.
+pub fn distance_field<S, T>(map: &[S], sources: impl IntoIterator<Item = Location>, passable: impl Fn(&T) -> bool) -> Map<Option<u32>>
+where
+    S: AsRef<[T]>,
+{
+    distance_field_with(map, sources, passable, FieldOptions::default())
+}
+
+pub fn distance_field_with<S, T>(
+    map: &[S],
+    sources: impl IntoIterator<Item = Location>,
+    passable: impl Fn(&T) -> bool,
+    options: FieldOptions,
+) -> Map<Option<u32>>
+where
+    S: AsRef<[T]>,
+{
+    let grid = Grid::from_map(map);
+    let mut field = grid.new_map(None);
+    let mut queue = VecDeque::new();
+
+    for s in sources {
+        if field[s[0]][s[1]].is_none() {
+            field[s[0]][s[1]] = Some(0);
+            queue.push_back(s);
+        }
+    }
+
+    while let Some(loc) = queue.pop_front() {
+        if options.target == Some(loc) {
+            break;
+        }
+
+        let next = field[loc[0]][loc[1]].unwrap() + 1;
+        for n in field_neighbors(&grid, loc, options.diagonal) {
+            if field[n[0]][n[1]].is_none() && passable(&map[n[0]].as_ref()[n[1]]) {
+                field[n[0]][n[1]] = Some(next);
+                queue.push_back(n);
+            }
+        }
+    }
+
+    field
+}
+
+/// Walks downhill through `field` from `target` to a source, returning the path in
+/// source-to-target order.
+pub fn path_from_field(field: &Map<Option<u32>>, target: Location, diagonal: bool) -> Option<Vec<Location>> {
+    let grid = Grid::from_map(field);
+    let mut dist = field[target[0]][target[1]]?;
+    let mut path = vec![target];
+    let mut loc = target;
+
+    while dist > 0 {
+        loc = field_neighbors(&grid, loc, diagonal).find(|n| field[n[0]][n[1]] == Some(dist - 1))?;
+        path.push(loc);
+        dist -= 1;
+    }
+
+    path.reverse();
+    Some(path)
+}
+
+#[test]
+fn check_distance_field() {
+    let bytes = [[5, 4], [4, 2], [4, 5], [3, 0], [2, 1], [6, 3], [2, 4], [1, 5], [0, 6], [3, 3], [2, 6], [5, 1]];
+    let mut memory = vec![vec![false; 7]; 7];
+    for [x, y] in bytes {
+        memory[y][x] = true;
+    }
+
+    let field = distance_field(&memory, [[0, 0]], |&corrupted| !corrupted);
+    assert_eq!(field[6][6], Some(22));
+
+    let path = path_from_field(&field, [6, 6], false).unwrap();
+    assert_eq!(path.len(), 23);
+    assert_eq!((path[0], path[22]), ([0, 0], [6, 6]));
+
+    let options = FieldOptions { diagonal: true, target: Some([6, 6]) };
+    let field = distance_field_with(&memory, [[0, 0], [0, 6]], |&corrupted| !corrupted, options);
+    assert!(field[6][6].is_some_and(|d| d < 22));
+}
+
+#[test]
+fn check_reindeer_maze() {
+    use crate::{CardinalDirectionName, Grid, Location};
+    use std::collections::HashSet;
+    use CardinalDirectionName::*;
+
+    struct Maze<'a> {
+        map: Vec<&'a [u8]>,
+        grid: Grid,
+        end: Location,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Reindeer(Location, CardinalDirectionName);
+
+    impl SearchState for Reindeer {
+        type Cost = u64;
+        type Context = Maze<'static>;
+
+        fn successors(&self, maze: &Maze, out: &mut Vec<(Self, u64)>) {
+            if let Some(next) = maze.grid.add_cardinal(self.0, self.1) {
+                if maze.map[next[0]][next[1]] != b'#' {
+                    out.push((Reindeer(next, self.1), 1));
+                }
+            }
+
+            let turns = match self.1 { N | S => [E, W], E | W => [N, S] };
+            out.extend(turns.map(|d| (Reindeer(self.0, d), 1000)));
+        }
+
+        fn is_goal(&self, maze: &Maze) -> bool {
+            self.0 == maze.end
+        }
+
+        fn heuristic(&self, maze: &Maze) -> u64 {
+            crate::taxicab_distance(self.0, maze.end) as u64
+        }
+    }
+
+    let map: Vec<&[u8]> = [
+        "###############",
+        "#.......#....E#",
+        "#.#.###.#.###.#",
+        "#.....#.#...#.#",
+        "#.###.#####.#.#",
+        "#.#.#.......#.#",
+        "#.#.#####.###.#",
+        "#...........#.#",
+        "###.#.#####.#.#",
+        "#...#.....#.#.#",
+        "#.#.#.###.#.#.#",
+        "#.....#...#.#.#",
+        "#.###.#.#.#.#.#",
+        "#S..#.....#...#",
+        "###############",
+    ].map(|l| l.as_bytes()).to_vec();
+
+    let grid = Grid::from_map(&map);
+    let maze = Maze { map, grid, end: [1, 13] };
+
+    let result = search(&maze, [Reindeer([13, 1], E)]).unwrap();
+    assert_eq!(result.cost, 7036);
+    assert_eq!(result.path().first(), Some(&Reindeer([13, 1], E)));
+
+    let tiles: HashSet<Location> = result.states_on_optimal_paths().into_iter().map(|r| r.0).collect();
+    assert_eq!(tiles.len(), 45);
+    assert_eq!(result.optimal_path_count(), Some(3));
+}
+
+#[test]
+fn check_zero_cost_ties() {
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Vertex(usize);
+
+    impl SearchState for Vertex {
+        type Cost = u32;
+        type Context = [Vec<(usize, u32)>];
+
+        fn successors(&self, edges: &Self::Context, out: &mut Vec<(Self, u32)>) {
+            out.extend(edges[self.0].iter().map(|&(to, cost)| (Vertex(to), cost)));
+        }
+
+        fn is_goal(&self, edges: &Self::Context) -> bool {
+            self.0 == edges.len() - 1
+        }
+    }
+
+    // 2 settles through 0 before 1 is popped, but 1 -> 2 ties it.
+    let edges = [vec![(1, 0), (2, 0)], vec![(2, 0)], vec![(3, 1)], vec![]];
+    let result = search(&edges[..], [Vertex(0)]).unwrap();
+    assert_eq!(result.cost, 1);
+    assert_eq!(result.optimal_path_count(), Some(2));
+    assert_eq!(result.states_on_optimal_paths().len(), 4);
+
+    // A free loop between 1 and 2 allows infinitely many optimal walks.
+    let edges = [vec![(1, 0)], vec![(2, 0)], vec![(1, 0), (3, 1)], vec![]];
+    assert_eq!(search(&edges[..], [Vertex(0)]).unwrap().optimal_path_count(), None);
+}
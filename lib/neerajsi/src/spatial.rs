@@ -0,0 +1,221 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+pub type Point<const K: usize> = [i64; K];
+
+pub fn squared_distance<const K: usize>(a: &Point<K>, b: &Point<K>) -> u64 {
+    a.iter().zip(b).map(|(x, y)| x.abs_diff(*y).pow(2)).sum()
+}
+
+/// A k-d tree over integer points. Query results are `(squared distance, point index)`.
+#[derive(Debug, Clone)]
+pub struct KdTree<const K: usize> {
+    points: Vec<Point<K>>,
+    // Implicit tree: the median of each range is its node, split on axis `depth % K`.
+    order: Vec<usize>,
+}
+
+impl<const K: usize> KdTree<K> {
+    pub fn new(points: &[Point<K>]) -> Self {
+        fn build<const K: usize>(points: &[Point<K>], order: &mut [usize], depth: usize) {
+            if order.len() <= 1 {
+                return;
+            }
+
+            let axis = depth % K;
+            let mid = order.len() / 2;
+            order.select_nth_unstable_by_key(mid, |&i| points[i][axis]);
+
+            let (left, right) = order.split_at_mut(mid);
+            build(points, left, depth + 1);
+            build(points, &mut right[1..], depth + 1);
+        }
+
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        build(points, &mut order, 0);
+        Self { points: points.to_vec(), order }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, index: usize) -> &Point<K> {
+        &self.points[index]
+    }
+
+    /// The `k` points closest to `query`, nearest first. Includes `query` itself if it is
+    /// one of the points.
+    pub fn nearest(&self, query: &Point<K>, k: usize) -> Vec<(u64, usize)> {
+        let mut best: BinaryHeap<(u64, usize)> = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.nearest_in(query, k, 0, self.order.len(), 0, &mut best);
+        }
+
+        best.into_sorted_vec()
+    }
+
+    fn nearest_in(&self, query: &Point<K>, k: usize, lo: usize, hi: usize, depth: usize, best: &mut BinaryHeap<(u64, usize)>) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let index = self.order[mid];
+        let d = squared_distance(query, &self.points[index]);
+        if best.len() < k {
+            best.push((d, index));
+        } else if (d, index) < *best.peek().unwrap() {
+            best.pop();
+            best.push((d, index));
+        }
+
+        let axis = depth % K;
+        let diff = query[axis] - self.points[index][axis];
+        let (near, far) = if diff < 0 { ((lo, mid), (mid + 1, hi)) } else { ((mid + 1, hi), (lo, mid)) };
+
+        self.nearest_in(query, k, near.0, near.1, depth + 1, best);
+        if best.len() < k || diff.unsigned_abs().pow(2) <= best.peek().unwrap().0 {
+            self.nearest_in(query, k, far.0, far.1, depth + 1, best);
+        }
+    }
+
+    /// Every point within `radius_squared` of `query`, nearest first.
+    pub fn within_radius(&self, query: &Point<K>, radius_squared: u64) -> Vec<(u64, usize)> {
+        let mut found = Vec::new();
+        let mut stack = vec![(0, self.order.len(), 0)];
+
+        while let Some((lo, hi, depth)) = stack.pop() {
+            if lo >= hi {
+                continue;
+            }
+
+            let mid = lo + (hi - lo) / 2;
+            let index = self.order[mid];
+            let d = squared_distance(query, &self.points[index]);
+            if d <= radius_squared {
+                found.push((d, index));
+            }
+
+            let axis = depth % K;
+            let diff = query[axis] - self.points[index][axis];
+            let (near, far) = if diff < 0 { ((lo, mid), (mid + 1, hi)) } else { ((mid + 1, hi), (lo, mid)) };
+
+            stack.push((near.0, near.1, depth + 1));
+            if diff.unsigned_abs().pow(2) <= radius_squared {
+                stack.push((far.0, far.1, depth + 1));
+            }
+        }
+
+        found.sort_unstable();
+        found
+    }
+
+    /// Every pair of points in order of increasing distance, without building the whole
+    /// O(n²) list up front.
+    pub fn closest_pairs(&self) -> ClosestPairs<'_, K> {
+        let mut pairs = ClosestPairs { tree: self, buffers: vec![Vec::new(); self.len()], fetched: vec![0; self.len()], heap: BinaryHeap::new() };
+        for i in 0..self.len() {
+            pairs.advance(i);
+        }
+        pairs
+    }
+}
+
+/// Iterator returned by [`KdTree::closest_pairs`], yielding `(squared distance, (a, b))`
+/// with `a < b`.
+#[derive(Debug, Clone)]
+pub struct ClosestPairs<'a, const K: usize> {
+    tree: &'a KdTree<K>,
+    // Upcoming partners of each point (all with a larger index), farthest first.
+    buffers: Vec<Vec<(u64, usize)>>,
+    fetched: Vec<usize>,
+    heap: BinaryHeap<Reverse<(u64, usize, usize)>>,
+}
+
+impl<const K: usize> ClosestPairs<'_, K> {
+    fn advance(&mut self, i: usize) {
+        if self.buffers[i].is_empty() && self.fetched[i] < self.tree.len() {
+            // Each refill doubles the neighbour count; the ones already handed out are skipped.
+            let k = (self.fetched[i] * 2).max(8).min(self.tree.len());
+            let skip = self.fetched[i];
+            let mut next: Vec<(u64, usize)> = self.tree.nearest(&self.tree.points[i], k).into_iter().skip(skip).filter(|&(_, j)| j > i).collect();
+            next.reverse();
+            self.fetched[i] = k;
+            self.buffers[i] = next;
+        }
+
+        if let Some(&(d, j)) = self.buffers[i].last() {
+            self.heap.push(Reverse((d, i, j)));
+        } else if self.fetched[i] < self.tree.len() {
+            self.advance(i);
+        }
+    }
+}
+
+impl<const K: usize> Iterator for ClosestPairs<'_, K> {
+    type Item = (u64, (usize, usize));
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((d, i, j)) = self.heap.pop()?;
+        self.buffers[i].pop();
+        self.advance(i);
+        Some((d, (i, j)))
+    }
+}
+
+#[test]
+fn check_junction_boxes() {
+    use crate::connectivity::UnionFind;
+
+    let points: Vec<Point<3>> = [
+        [162, 817, 812], [57, 618, 57], [906, 360, 560], [592, 479, 940], [352, 342, 300], [466, 668, 158], [542, 29, 236],
+        [431, 825, 988], [739, 650, 466], [52, 470, 668], [216, 146, 977], [819, 987, 18], [117, 168, 530], [805, 96, 715],
+        [346, 949, 466], [970, 615, 88], [941, 993, 340], [862, 61, 35], [984, 92, 344], [425, 690, 689],
+    ].to_vec();
+
+    let tree = KdTree::new(&points);
+
+    let mut all_pairs: Vec<u64> = (0..points.len())
+        .flat_map(|a| (a + 1..points.len()).map(move |b| (a, b)))
+        .map(|(a, b)| squared_distance(&points[a], &points[b]))
+        .collect();
+    all_pairs.sort_unstable();
+    assert_eq!(tree.closest_pairs().map(|(d, _)| d).collect::<Vec<_>>(), all_pairs);
+
+    let mut uf = UnionFind::new(points.len());
+    for (_, (a, b)) in tree.closest_pairs().take(10) {
+        uf.union(a, b);
+    }
+
+    let mut sizes = Vec::new();
+    for i in 0..points.len() {
+        if uf.find(i) == i {
+            sizes.push(uf.set_size(i));
+        }
+    }
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    assert_eq!(sizes[..3].iter().product::<usize>(), 40);
+
+    let mut uf = UnionFind::new(points.len());
+    let mut merges = 0;
+    let last = tree.closest_pairs().find(|&(_, (a, b))| {
+        merges += uf.union(a, b) as usize;
+        merges == points.len() - 1
+    });
+    let (_, (a, b)) = last.unwrap();
+    assert_eq!(points[a][0] * points[b][0], 25272);
+
+    let near = tree.nearest(&[160, 800, 800], 2);
+    assert_eq!(near[0].1, 0);
+    assert_eq!(tree.within_radius(&[160, 800, 800], near[1].0), near);
+
+    // Fewer points than the first neighbour batch.
+    let few = KdTree::new(&points[..5]);
+    assert_eq!(few.closest_pairs().count(), 10);
+    assert_eq!(KdTree::new(&points[..1]).closest_pairs().count(), 0);
+}